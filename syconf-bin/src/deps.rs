@@ -0,0 +1,134 @@
+use std::path::{Path, PathBuf};
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use syconf_lib::Dependencies;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("deps")
+        .about("Lists the files a configuration depends on, i.e. its transitive imports and the files read by read_file")
+        .arg(
+            Arg::with_name("input")
+                .help("Input file name")
+                .required(true)
+                .value_name("CONFIG_FILE"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .short("f")
+                .help("Output format, dot prints the import graph")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(&["list", "dot"])
+                .default_value("list"),
+        )
+        .args(&crate::evaluation_args())
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), String> {
+    let options = crate::evaluation_options(matches)?;
+    syconf_lib::parse_file_with_options(matches.value_of("input").unwrap(), &options)
+        .map_err(|e| e.to_string())?;
+    match matches.value_of("format").unwrap() {
+        "list" => {
+            for file in options.dependencies.files() {
+                println!("{}", display(&file));
+            }
+        }
+        _ => print!("{}", dot(&options.dependencies)),
+    }
+    Ok(())
+}
+
+fn dot(deps: &Dependencies) -> String {
+    let imports = deps.imports();
+    let mut out = "digraph imports {\n".to_string();
+    for file in deps.files() {
+        // Files that are not imported are the evaluated file and the files read by read_file.
+        let shape = match imports.iter().any(|(_, to)| *to == file) {
+            true => "box",
+            false if file.extension() == Some("sy".as_ref()) => "doubleoctagon",
+            false => "note",
+        };
+        out += &format!("    {:?} [shape={}];\n", display(&file), shape);
+    }
+    for (from, to) in imports {
+        out += &format!("    {:?} -> {:?};\n", display(&from), display(&to));
+    }
+    out + "}\n"
+}
+
+/// Returns a Makefile rule with the dependencies of the targets.
+pub fn depfile(targets: &[PathBuf], deps: &Dependencies) -> String {
+    let escape = |path: &Path| {
+        display(path)
+            .replace('$', "$$")
+            .replace('#', "\\#")
+            .replace(' ', "\\ ")
+    };
+    let targets: Vec<String> = targets.iter().map(|x| escape(x)).collect();
+    let prerequisites: Vec<String> = deps.files().iter().map(|x| escape(x)).collect();
+    format!("{}: {}\n", targets.join(" "), prerequisites.join(" "))
+}
+
+// Paths in the current directory are shown relative to it.
+fn display(path: &Path) -> String {
+    std::env::current_dir()
+        .ok()
+        .and_then(|dir| path.strip_prefix(dir).ok())
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+#[cfg(test)]
+fn dependencies(dir: &Path) -> Dependencies {
+    std::fs::write(
+        dir.join("main.sy"),
+        format!(
+            r#"[import "./my lib$#.sy", read_file("{}")]"#,
+            dir.join("notes.txt").display()
+        ),
+    )
+    .unwrap();
+    std::fs::write(dir.join("my lib$#.sy"), "1").unwrap();
+    std::fs::write(dir.join("notes.txt"), "notes").unwrap();
+    let options = syconf_lib::Options::default();
+    syconf_lib::parse_file_with_options(dir.join("main.sy").to_str().unwrap(), &options).unwrap();
+    options.dependencies
+}
+
+#[test]
+fn makefile_rule() {
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path().canonicalize().unwrap();
+    let deps = dependencies(&dir);
+    assert_eq!(
+        depfile(&[dir.join("out 1.json")], &deps),
+        format!(
+            "{0}/out\\ 1.json: {0}/main.sy {0}/my\\ lib$$\\#.sy {0}/notes.txt\n",
+            dir.display()
+        )
+    );
+}
+
+#[test]
+fn import_graph() {
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path().canonicalize().unwrap();
+    let deps = dependencies(&dir);
+    assert_eq!(
+        dot(&deps),
+        format!(
+            r#"digraph imports {{
+    "{0}/main.sy" [shape=doubleoctagon];
+    "{0}/my lib$#.sy" [shape=box];
+    "{0}/notes.txt" [shape=note];
+    "{0}/main.sy" -> "{0}/my lib$#.sy";
+}}
+"#,
+            dir.display()
+        )
+    );
+}
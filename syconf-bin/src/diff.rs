@@ -0,0 +1,53 @@
+use std::path::Path;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use syconf_lib::{Options, Value};
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("diff")
+        .about("Prints structural differences between two configurations, .json/.yaml/.toml files are read as data")
+        .arg(
+            Arg::with_name("old")
+                .help("Old configuration")
+                .required(true)
+                .value_name("OLD_FILE"),
+        )
+        .arg(
+            Arg::with_name("new")
+                .help("New configuration")
+                .required(true)
+                .value_name("NEW_FILE"),
+        )
+        .arg(
+            Arg::with_name("exit-code")
+                .long("exit-code")
+                .help("Exit with 1 if there are differences"),
+        )
+        .args(&crate::evaluation_args())
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), String> {
+    let options = crate::evaluation_options(matches)?;
+    let old = load(matches.value_of("old").unwrap(), &options)?;
+    let new = load(matches.value_of("new").unwrap(), &options)?;
+    let changes = syconf_lib::diff(&old, &new);
+    for change in &changes {
+        println!("{}", change);
+    }
+    if !changes.is_empty() && matches.is_present("exit-code") {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn load(file: &str, options: &Options) -> Result<Value, String> {
+    let read = || std::fs::read_to_string(file).map_err(|e| format!("cannot read {}: {}", file, e));
+    let parse_error = |e: &dyn std::fmt::Display| format!("cannot parse {}: {}", file, e);
+    match Path::new(file).extension().and_then(|x| x.to_str()) {
+        Some("json") => serde_json::from_str(&read()?).map_err(|e| parse_error(&e)),
+        Some("yaml") | Some("yml") => serde_yaml::from_str(&read()?).map_err(|e| parse_error(&e)),
+        Some("toml") => toml::from_str(&read()?).map_err(|e| parse_error(&e)),
+        _ => syconf_lib::parse_file_with_options(file, options).map_err(|e| e.to_string()),
+    }
+}
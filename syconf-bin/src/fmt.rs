@@ -0,0 +1,56 @@
+use std::io::{self, Read};
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use crate::output;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("fmt")
+        .about("Formats configuration files in place, '-' formats stdin to stdout")
+        .arg(
+            Arg::with_name("files")
+                .help("Files to format")
+                .required(true)
+                .multiple(true)
+                .value_name("CONFIG_FILE"),
+        )
+        .arg(
+            Arg::with_name("check").long("check").help(
+                "Do not write the files, but print a diff and fail if a file is not formatted",
+            ),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), String> {
+    let check = matches.is_present("check");
+    let mut unformatted = 0;
+    for file in matches.values_of("files").unwrap() {
+        if file == "-" {
+            let mut source = String::new();
+            io::stdin()
+                .read_to_string(&mut source)
+                .map_err(|e| e.to_string())?;
+            let formatted = syconf_lib::format_string(&source).map_err(|e| e.to_string())?;
+            if !check {
+                print!("{}", formatted);
+            } else if formatted != source {
+                output::print_diff("<stdin>", &source, "<stdin>", &formatted);
+                unformatted += 1;
+            }
+            continue;
+        }
+        let formatted = syconf_lib::format_file(file).map_err(|e| e.to_string())?;
+        if check {
+            if !output::check_file(file.as_ref(), &formatted) {
+                unformatted += 1;
+            }
+        } else {
+            std::fs::write(file, formatted).map_err(|e| format!("cannot write {}: {}", file, e))?;
+        }
+    }
+    match unformatted {
+        0 => Ok(()),
+        1 => Err("1 file is not formatted".to_string()),
+        n => Err(format!("{} files are not formatted", n)),
+    }
+}
@@ -1,11 +1,21 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::{env, io};
 
-use clap::{App, Arg};
+use clap::{App, AppSettings, Arg, ArgMatches};
 use tracing_subscriber::EnvFilter;
 
-use syconf_lib::SerializableValue;
+use syconf_lib::{Inputs, InputsReport, Options, Value};
+
+mod deps;
+mod diff;
+mod fmt;
+mod output;
+mod render;
+mod repl;
+mod watch;
 
 fn main() {
     let matches = App::new("syconf")
@@ -13,10 +23,27 @@ fn main() {
         .about("syconf converts syconf files into JSON/YAML/TOML")
         .arg(
             Arg::with_name("input")
-                .help("Input file name")
-                .required(true)
+                .help("Input file name, '-' reads from stdin")
+                .required_unless("eval")
                 .value_name("CONFIG_FILE"),
         )
+        .arg(
+            Arg::with_name("eval")
+                .long("eval")
+                .short("e")
+                .help("Evaluate the given expression instead of a file")
+                .takes_value(true)
+                .conflicts_with("input")
+                .value_name("EXPR"),
+        )
+        .arg(
+            Arg::with_name("path")
+                .long("path")
+                .short("p")
+                .help("Output only the value at the given path, e.g. spec.containers[0].image")
+                .takes_value(true)
+                .value_name("PATH"),
+        )
         .arg(
             Arg::with_name("output")
                 .long("output")
@@ -33,64 +60,321 @@ fn main() {
                 .help("Output format")
                 .takes_value(true)
                 .value_name("FORMAT")
-                .possible_values(&["json", "yaml", "yaml-stream", "toml", "text"])
+                .possible_values(output::FORMATS)
                 .default_value("json"),
         )
+        .arg(
+            Arg::with_name("multi-output")
+                .long("multi-output")
+                .short("m")
+                .help("Write every entry of the top-level hashmap into a file in DIR, the format is defined by the file extension")
+                .takes_value(true)
+                .conflicts_with("output")
+                .value_name("DIR"),
+        )
+        .arg(
+            Arg::with_name("prune")
+                .long("prune")
+                .help("Delete files from the multi-output directory that were not written by this run")
+                .requires("multi-output"),
+        )
+        .arg(
+            Arg::with_name("arg")
+                .long("arg")
+                .help("String argument of the top-level function, can be repeated")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("NAME=VALUE"),
+        )
+        .arg(
+            Arg::with_name("arg-json")
+                .long("arg-json")
+                .help("JSON argument of the top-level function, can be repeated")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("NAME=JSON"),
+        )
+        .arg(
+            Arg::with_name("arg-file")
+                .long("arg-file")
+                .help("Argument of the top-level function with the file content as a string, can be repeated")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("NAME=FILE"),
+        )
+        .arg(
+            Arg::with_name("set")
+                .long("set")
+                .help("Override a string value in the result, e.g. spec.containers[0].image=app:2, can be repeated")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("PATH=VALUE"),
+        )
+        .arg(
+            Arg::with_name("set-json")
+                .long("set-json")
+                .help("Override a value in the result with JSON, hashmaps are merged, can be repeated")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("PATH=JSON"),
+        )
+        .arg(
+            Arg::with_name("watch")
+                .long("watch")
+                .short("w")
+                .help("Convert again whenever the input file or one of the files it reads changes")
+                .requires("input")
+                .conflicts_with("check"),
+        )
+        .arg(
+            Arg::with_name("depfile")
+                .long("depfile")
+                .help("Write the files the output depends on as a Makefile rule")
+                .takes_value(true)
+                .value_name("FILE"),
+        )
+        .arg(
+            Arg::with_name("inputs-report")
+                .long("inputs-report")
+                .help("Write the environment variables, files and shell commands the evaluation used into a JSON report")
+                .takes_value(true)
+                .value_name("FILE"),
+        )
+        .arg(
+            Arg::with_name("frozen")
+                .long("frozen")
+                .help("Fail if an input of the evaluation differs from the given inputs report")
+                .takes_value(true)
+                .value_name("REPORT_FILE"),
+        )
+        .arg(
+            Arg::with_name("check")
+                .long("check")
+                .help("Do not write the output, but print a diff and fail if the output is not up to date"),
+        )
+        .args(&evaluation_args())
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .subcommand(render::subcommand())
+        .subcommand(diff::subcommand())
+        .subcommand(deps::subcommand())
+        .subcommand(repl::subcommand())
+        .subcommand(fmt::subcommand())
         .get_matches();
 
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::from_default_env())
         .init();
 
-    let result = match matches.value_of("input").unwrap() {
+    let subcommand_result = match matches.subcommand() {
+        ("render", Some(matches)) => Some(render::run(matches)),
+        ("diff", Some(matches)) => Some(diff::run(matches)),
+        ("deps", Some(matches)) => Some(deps::run(matches)),
+        ("repl", Some(matches)) => Some(repl::run(matches)),
+        ("fmt", Some(matches)) => Some(fmt::run(matches)),
+        _ => None,
+    };
+    if let Some(result) = subcommand_result {
+        if let Err(e) = result {
+            eprintln!("ERROR: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mut options = evaluation_options(&matches).unwrap_or_else(|e| {
+        eprintln!("ERROR: {}", e);
+        std::process::exit(1);
+    });
+    options.args = Some(top_level_args(&matches).unwrap_or_else(|e| {
+        eprintln!("ERROR: {}", e);
+        std::process::exit(1);
+    }));
+    if let Some(report) = matches.value_of("frozen") {
+        options.inputs = Inputs::frozen(read_inputs_report(report).unwrap_or_else(|e| {
+            eprintln!("ERROR: {}", e);
+            std::process::exit(1);
+        }));
+    }
+
+    if matches.is_present("watch") {
+        watch::run(&matches, &options);
+    }
+    if let Err(e) = convert(&matches, &options) {
+        eprintln!("ERROR: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Evaluates the input and writes or checks the output.
+fn convert(matches: &ArgMatches, options: &Options) -> Result<(), String> {
+    let result = match matches.value_of("input").unwrap_or("-") {
+        _ if matches.is_present("eval") => {
+            syconf_lib::parse_string_with_options(matches.value_of("eval").unwrap(), options)
+        }
         "-" => {
             let mut s = String::new();
             io::stdin().read_to_string(&mut s).unwrap();
-            syconf_lib::parse_string(&s)
+            syconf_lib::parse_string_with_options(&s, options)
         }
-        file => syconf_lib::parse_file(file),
+        file => syconf_lib::parse_file_with_options(file, options),
     };
 
-    let val = match result {
-        Ok(val) => val.to_serializable(),
-        Err(e) => {
-            eprintln!("ERROR: {}", e);
-            std::process::exit(1);
+    let val = result
+        .and_then(|val| apply_overrides(val, matches).map_err(Into::into))
+        .and_then(|val| match matches.value_of("path") {
+            Some(path) => val.get_path(path).cloned(),
+            None => Ok(val),
+        })
+        .map_err(|e| e.to_string())?
+        .to_serializable();
+
+    if let Some(report) = matches.value_of("inputs-report") {
+        let json = serde_json::to_string_pretty(&options.inputs.report()).unwrap();
+        std::fs::write(report, json).map_err(|e| format!("cannot write {}: {}", report, e))?;
+    }
+
+    if let Some(dir) = matches.value_of("multi-output") {
+        let files = output::files(&val)?;
+        let prune = matches.is_present("prune");
+        if matches.is_present("check") {
+            return output::check_files(Path::new(dir), &files, prune);
         }
-    };
+        output::write_files(Path::new(dir), &files, prune)?;
+        let targets: Vec<PathBuf> = files
+            .iter()
+            .map(|(name, _)| Path::new(dir).join(name))
+            .collect();
+        return write_depfile(matches, &targets, options);
+    }
+
+    let ser = output::serialize(&val, matches.value_of("format").unwrap())?;
 
-    let ser = match matches.value_of("format").unwrap() {
-        "json" => serde_json::to_string(&val).unwrap(),
-        "yaml" => serde_yaml::to_string(&val).unwrap(),
-        "yaml-stream" => to_yaml_stream(&val),
-        "toml" => toml::ser::to_string(&val).unwrap(),
-        "text" => {
-            if let SerializableValue::String(s) = val {
-                s.to_string()
+    match matches.value_of("output").unwrap() {
+        "stdout" if matches.is_present("check") => {
+            Err("--check requires an output file or directory".to_string())
+        }
+        file if matches.is_present("check") => {
+            if output::check_file(Path::new(file), &ser) {
+                Ok(())
             } else {
-                eprintln!("ERROR: resulting error is not a string");
-                std::process::exit(1);
+                Err(format!("{} is out of date", file))
             }
         }
-        _ => unreachable!(),
-    };
-
-    match matches.value_of("output").unwrap() {
-        "stdout" => io::stdout().write_all(ser.as_bytes()).unwrap(),
-        file => File::create(file)
-            .unwrap()
+        "stdout" if matches.is_present("depfile") => {
+            Err("--depfile requires an output file or directory".to_string())
+        }
+        "stdout" => io::stdout()
             .write_all(ser.as_bytes())
-            .unwrap(),
+            .map_err(|e| e.to_string()),
+        file => {
+            File::create(file)
+                .and_then(|mut f| f.write_all(ser.as_bytes()))
+                .map_err(|e| format!("cannot write {}: {}", file, e))?;
+            write_depfile(matches, &[PathBuf::from(file)], options)
+        }
     }
 }
 
-fn to_yaml_stream(val: &SerializableValue) -> String {
-    match val {
-        SerializableValue::List(list) => list
-            .iter()
-            .map(|x| serde_yaml::to_string(x).unwrap())
-            .collect::<Vec<String>>()
-            .join("\n\n"),
-        v => serde_yaml::to_string(v).unwrap(),
+fn write_depfile(
+    matches: &ArgMatches,
+    targets: &[PathBuf],
+    options: &Options,
+) -> Result<(), String> {
+    match matches.value_of("depfile") {
+        Some(depfile) => std::fs::write(depfile, deps::depfile(targets, &options.dependencies))
+            .map_err(|e| format!("cannot write {}: {}", depfile, e)),
+        None => Ok(()),
+    }
+}
+
+fn read_inputs_report(file: &str) -> Result<InputsReport, String> {
+    let content =
+        std::fs::read_to_string(file).map_err(|e| format!("cannot read {}: {}", file, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("cannot parse {}: {}", file, e))
+}
+
+/// Arguments that control the evaluation, shared by the subcommands.
+fn evaluation_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("lib-dir")
+            .long("lib-dir")
+            .short("I")
+            .help("Directory to look up imports in, can be repeated. Directories from SYCONF_PATH are searched afterwards")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .value_name("DIR"),
+        Arg::with_name("ext")
+            .long("ext")
+            .help("External variable accessible with the ext function, can be repeated")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .value_name("NAME=VALUE"),
+    ]
+}
+
+fn evaluation_options(matches: &ArgMatches) -> Result<Options, String> {
+    let mut options = Options::from_env();
+    if let Some(dirs) = matches.values_of("lib-dir") {
+        options.lib_dirs.splice(0..0, dirs.map(PathBuf::from));
+    }
+    for ext in matches.values_of("ext").into_iter().flatten() {
+        let (name, value) = ext
+            .split_once('=')
+            .ok_or_else(|| format!("--ext expects NAME=VALUE, but was '{}'", ext))?;
+        options
+            .ext_vars
+            .insert(name.to_string(), Value::String(value.into()));
+    }
+    Ok(options)
+}
+
+fn top_level_args(matches: &ArgMatches) -> Result<HashMap<String, Value>, String> {
+    let mut args = HashMap::new();
+    for flag in &["arg", "arg-json", "arg-file"] {
+        for arg in matches.values_of(flag).into_iter().flatten() {
+            let (name, value) = arg
+                .split_once('=')
+                .ok_or_else(|| format!("--{} expects NAME=VALUE, but was '{}'", flag, arg))?;
+            let value = match *flag {
+                "arg" => Value::String(value.into()),
+                "arg-json" => serde_json::from_str(value)
+                    .map_err(|e| format!("--{} {}: cannot parse JSON: {}", flag, name, e))?,
+                _ => std::fs::read_to_string(value)
+                    .map(|content| Value::String(content.into()))
+                    .map_err(|e| format!("--{} {}: cannot read file: {}", flag, name, e))?,
+            };
+            args.insert(name.to_string(), value);
+        }
+    }
+    Ok(args)
+}
+
+fn apply_overrides(mut val: Value, matches: &ArgMatches) -> Result<Value, String> {
+    let mut overrides = Vec::new();
+    for flag in &["set", "set-json"] {
+        if let (Some(values), Some(indices)) = (matches.values_of(flag), matches.indices_of(flag)) {
+            overrides.extend(indices.zip(values).map(|(ix, value)| (ix, *flag, value)));
+        }
+    }
+    // Overrides are applied in the order they are given on the command line.
+    overrides.sort_by_key(|(ix, _, _)| *ix);
+    for (_, flag, arg) in overrides {
+        let (path, value) = arg
+            .split_once('=')
+            .ok_or_else(|| format!("--{} expects PATH=VALUE, but was '{}'", flag, arg))?;
+        let value = match flag {
+            "set" => Value::String(value.into()),
+            _ => serde_json::from_str(value)
+                .map_err(|e| format!("--{} {}: cannot parse JSON: {}", flag, path, e))?,
+        };
+        val = val.merge_at_path(path, value).map_err(|e| e.to_string())?;
     }
+    Ok(val)
 }
@@ -0,0 +1,321 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use similar::TextDiff;
+use syconf_lib::SerializableValue;
+
+pub const FORMATS: &[&str] = &["json", "yaml", "yaml-stream", "toml", "text"];
+
+/// Extensions of the files written in the multi-file mode, with their formats.
+const FILE_FORMATS: &[(&str, &str)] = &[
+    ("json", "json"),
+    ("yaml", "yaml"),
+    ("yml", "yaml"),
+    ("toml", "toml"),
+    ("txt", "text"),
+];
+
+pub fn serialize(val: &SerializableValue, format: &str) -> Result<String, String> {
+    Ok(match format {
+        "json" => serde_json::to_string(val).map_err(|e| e.to_string())?,
+        "yaml" => serde_yaml::to_string(val).map_err(|e| e.to_string())?,
+        "yaml-stream" => to_yaml_stream(val)?,
+        "toml" => toml::ser::to_string(val).map_err(|e| e.to_string())?,
+        "text" => match val {
+            SerializableValue::String(s) => s.to_string(),
+            _ => return Err("resulting value is not a string".to_string()),
+        },
+        _ => unreachable!(),
+    })
+}
+
+fn to_yaml_stream(val: &SerializableValue) -> Result<String, String> {
+    match val {
+        SerializableValue::List(list) => Ok(list
+            .iter()
+            .map(|x| serde_yaml::to_string(x).map_err(|e| e.to_string()))
+            .collect::<Result<Vec<String>, String>>()?
+            .join("\n\n")),
+        v => serde_yaml::to_string(v).map_err(|e| e.to_string()),
+    }
+}
+
+/// Returns the file extension for the output format.
+pub fn extension(format: &str) -> &'static str {
+    match format {
+        "json" => "json",
+        "yaml" | "yaml-stream" => "yaml",
+        "toml" => "toml",
+        "text" => "txt",
+        _ => unreachable!(),
+    }
+}
+
+/// Serializes every entry of the top-level hashmap in the format implied by the file extension of its key.
+pub fn files(val: &SerializableValue) -> Result<Vec<(PathBuf, String)>, String> {
+    let hm = match val {
+        SerializableValue::HashMap(hm) => hm,
+        _ => return Err("multi-file output requires a hashmap of file names".to_string()),
+    };
+    hm.iter()
+        .map(|(name, value)| {
+            let path = PathBuf::from(name.as_ref());
+            if !is_relative_file_name(&path) {
+                return Err(format!("'{}' is not a relative file name", name));
+            }
+            let format = file_format(&path).ok_or_else(|| {
+                format!(
+                    "'{}' has no supported extension (json, yaml, yml, toml, txt)",
+                    name
+                )
+            })?;
+            let content = serialize(value, format).map_err(|e| format!("{}: {}", name, e))?;
+            Ok((path, content))
+        })
+        .collect()
+}
+
+fn file_format(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?;
+    FILE_FORMATS
+        .iter()
+        .find(|(e, _)| *e == ext)
+        .map(|(_, format)| *format)
+}
+
+/// Lists the files written into the output directory, so that later runs know which files they may prune.
+const MANIFEST: &str = ".syconf-outputs";
+
+/// Writes the files into `dir` and records them in its manifest. If `prune` is set,
+/// files recorded by previous runs that are not part of `files` are deleted from `dir`.
+pub fn write_files(dir: &Path, files: &[(PathBuf, String)], prune: bool) -> Result<(), String> {
+    let stale = stale_files(dir, files)?;
+    for (name, content) in files {
+        let path = dir.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("cannot create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&path, content).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+    }
+    let mut manifest: Vec<&Path> = files.iter().map(|(name, _)| name.as_path()).collect();
+    if prune {
+        for name in &stale {
+            let path = dir.join(name);
+            fs::remove_file(&path)
+                .map_err(|e| format!("cannot remove {}: {}", path.display(), e))?;
+        }
+    } else {
+        // Files that are kept can still be pruned by a later run.
+        manifest.extend(stale.iter().map(PathBuf::as_path));
+    }
+    write_manifest(dir, &manifest)
+}
+
+/// Returns the files recorded in the manifest of `dir` that still exist, but are not in `files`.
+/// The paths are relative to `dir`.
+pub fn stale_files(dir: &Path, files: &[(PathBuf, String)]) -> Result<Vec<PathBuf>, String> {
+    let written: HashSet<&Path> = files.iter().map(|(name, _)| name.as_path()).collect();
+    Ok(read_manifest(dir)?
+        .into_iter()
+        .filter(|name| !written.contains(name.as_path()) && dir.join(name).is_file())
+        .collect())
+}
+
+fn read_manifest(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let path = dir.join(MANIFEST);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(format!("cannot read {}: {}", path.display(), e)),
+    };
+    // Entries that do not name a file inside the directory are ignored.
+    Ok(content
+        .lines()
+        .map(PathBuf::from)
+        .filter(|x| is_relative_file_name(x) && file_format(x).is_some())
+        .collect())
+}
+
+fn write_manifest(dir: &Path, names: &[&Path]) -> Result<(), String> {
+    let mut lines: Vec<String> = names.iter().map(|x| x.display().to_string()).collect();
+    lines.sort();
+    lines.dedup();
+    let path = dir.join(MANIFEST);
+    fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+    fs::write(
+        &path,
+        lines.iter().map(|x| format!("{}\n", x)).collect::<String>(),
+    )
+    .map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+fn is_relative_file_name(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Compares the files with their would-be content and prints a unified diff for every file that differs.
+/// If `prune` is set, the stale files recorded in the manifest of `dir` are reported as well.
+pub fn check_files(dir: &Path, files: &[(PathBuf, String)], prune: bool) -> Result<(), String> {
+    let mut out_of_date = files
+        .iter()
+        .filter(|(name, content)| !check_file(&dir.join(name), content))
+        .count();
+    if prune {
+        for name in stale_files(dir, files)? {
+            let path = dir.join(name);
+            let current = fs::read_to_string(&path).unwrap_or_default();
+            print_diff(&path.display().to_string(), &current, "/dev/null", "");
+            out_of_date += 1;
+        }
+    }
+    match out_of_date {
+        0 => Ok(()),
+        1 => Err("1 file is out of date".to_string()),
+        n => Err(format!("{} files are out of date", n)),
+    }
+}
+
+/// Prints a unified diff if the file content differs from `content`, a missing file is treated as empty.
+/// Returns true if the file is up to date.
+pub fn check_file(path: &Path, content: &str) -> bool {
+    let name = path.display().to_string();
+    match fs::read_to_string(path) {
+        Ok(current) if current == content => true,
+        Ok(current) => {
+            print_diff(&name, &current, &name, content);
+            false
+        }
+        Err(_) => {
+            print_diff("/dev/null", "", &name, content);
+            false
+        }
+    }
+}
+
+/// Prints a unified diff between the old and the new content.
+pub fn print_diff(old_name: &str, old: &str, new_name: &str, new: &str) {
+    print!(
+        "{}",
+        TextDiff::from_lines(old, new)
+            .unified_diff()
+            .header(old_name, new_name)
+    );
+}
+
+#[cfg(test)]
+fn write(dir: &Path, name: &str, content: &str) {
+    let path = dir.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+#[cfg(test)]
+fn output_files(source: &str) -> Result<Vec<(PathBuf, String)>, String> {
+    files(&syconf_lib::parse_string(source).unwrap().to_serializable())
+}
+
+#[test]
+fn files_by_extension() {
+    let mut out =
+        output_files(r#"{ "a/b.json": { x: 1 }, "c.txt": "text", "d.yml": [1] }"#).unwrap();
+    out.sort();
+    assert_eq!(
+        out,
+        vec![
+            (PathBuf::from("a/b.json"), r#"{"x":1}"#.to_string()),
+            (PathBuf::from("c.txt"), "text".to_string()),
+            (PathBuf::from("d.yml"), "---\n- 1\n".to_string()),
+        ]
+    );
+    assert_eq!(
+        output_files("[1]").unwrap_err(),
+        "multi-file output requires a hashmap of file names"
+    );
+    for name in &["../a.json", "/a.json", "a/../b.json", ""] {
+        assert_eq!(
+            output_files(&format!(r#"{{ "{}": 1 }}"#, name)).unwrap_err(),
+            format!("'{}' is not a relative file name", name)
+        );
+    }
+    assert_eq!(
+        output_files(r#"{ "a.ini": 1 }"#).unwrap_err(),
+        "'a.ini' has no supported extension (json, yaml, yml, toml, txt)"
+    );
+    assert_eq!(
+        output_files(r#"{ "a.txt": 1 }"#).unwrap_err(),
+        "a.txt: resulting value is not a string"
+    );
+}
+
+#[test]
+fn prune_only_written_files() {
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path();
+    write(dir, "Cargo.toml", "[package]");
+    write(dir, "sub/notes.txt", "notes");
+    let file = |name: &str| (PathBuf::from(name), format!("{}\n", name));
+
+    write_files(dir, &[file("a.json"), file("sub/b.yaml")], true).unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.join(MANIFEST)).unwrap(),
+        "a.json\nsub/b.yaml\n"
+    );
+    assert_eq!(
+        stale_files(dir, &[file("a.json")]).unwrap(),
+        vec![PathBuf::from("sub/b.yaml")]
+    );
+
+    // Without --prune the stale file stays recorded, so that a later run can prune it.
+    write_files(dir, &[file("a.json")], false).unwrap();
+    assert!(dir.join("sub/b.yaml").is_file());
+    assert_eq!(
+        fs::read_to_string(dir.join(MANIFEST)).unwrap(),
+        "a.json\nsub/b.yaml\n"
+    );
+
+    write_files(dir, &[file("a.json")], true).unwrap();
+    assert!(!dir.join("sub/b.yaml").exists());
+    assert!(dir.join("Cargo.toml").is_file());
+    assert!(dir.join("sub/notes.txt").is_file());
+    assert_eq!(fs::read_to_string(dir.join(MANIFEST)).unwrap(), "a.json\n");
+
+    // Manifest entries outside of the directory are never pruned.
+    write(dir, "inner/c.json", "{}");
+    fs::write(
+        dir.join("inner").join(MANIFEST),
+        "../Cargo.toml\n/etc/passwd\nc.json\n",
+    )
+    .unwrap();
+    assert_eq!(
+        stale_files(&dir.join("inner"), &[]).unwrap(),
+        vec![PathBuf::from("c.json")]
+    );
+}
+
+#[test]
+fn check_output_files() {
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path();
+    let path = dir.join("a.json");
+    assert!(!check_file(&path, "{}"));
+    write(dir, "a.json", "{}");
+    assert!(check_file(&path, "{}"));
+    assert!(!check_file(&path, "[]"));
+
+    let files = vec![(PathBuf::from("a.json"), "{}".to_string())];
+    write_files(dir, &files, false).unwrap();
+    // Files that are not recorded in the manifest are not stale.
+    write(dir, "b.json", "{}");
+    assert_eq!(check_files(dir, &files, true), Ok(()));
+    assert_eq!(
+        check_files(dir, &[], true).unwrap_err(),
+        "1 file is out of date"
+    );
+    assert_eq!(
+        check_files(dir, &[(PathBuf::from("c.json"), "1".to_string())], true).unwrap_err(),
+        "2 files are out of date"
+    );
+}
@@ -0,0 +1,120 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use syconf_lib::Value;
+
+use crate::output;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("render")
+        .about("Renders every .sy file of a directory tree that is not a library, imports are parsed only once")
+        .arg(
+            Arg::with_name("src")
+                .help("Directory with the configuration files")
+                .required(true)
+                .value_name("SRC_DIR"),
+        )
+        .arg(
+            Arg::with_name("out")
+                .long("out")
+                .help("Output directory, the structure of the source directory is mirrored")
+                .required(true)
+                .takes_value(true)
+                .value_name("OUT_DIR"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .short("f")
+                .help("Output format")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(output::FORMATS)
+                .default_value("json"),
+        )
+        .args(&crate::evaluation_args())
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), String> {
+    let options = crate::evaluation_options(matches)?;
+    let src = Path::new(matches.value_of("src").unwrap());
+    let out = Path::new(matches.value_of("out").unwrap());
+    let format = matches.value_of("format").unwrap();
+
+    let mut files = Vec::new();
+    find_configs(src, &mut files)?;
+    files.sort();
+
+    let mut failures = Vec::new();
+    let mut configs = Vec::new();
+    for file in &files {
+        match config_name(file) {
+            Ok(Some(name)) => configs.push(name),
+            Ok(None) => {}
+            Err(e) => failures.push(e),
+        }
+    }
+    let total = configs.len() + failures.len();
+    let values = syconf_lib::parse_files_with_options(&configs, &options);
+    for (file_name, value) in configs.iter().zip(values) {
+        if let Err(e) = value
+            .map_err(|e| e.to_string())
+            .and_then(|value| write_file(Path::new(file_name), &value, src, out, format))
+        {
+            failures.push(e);
+        }
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        for failure in &failures {
+            eprintln!("ERROR: {}", failure);
+        }
+        Err(format!("{} of {} files failed", failures.len(), total))
+    }
+}
+
+fn find_configs(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("cannot read {}: {}", dir.display(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.is_dir() {
+            find_configs(&path, files)?;
+        } else if path.extension() == Some("sy".as_ref()) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+// Returns the file name, or None if the file is a library that is not rendered.
+fn config_name(file: &Path) -> Result<Option<&str>, String> {
+    let file_name = file
+        .to_str()
+        .ok_or_else(|| format!("{}: path is not valid UTF-8", file.display()))?;
+    if syconf_lib::is_library_file(file_name).map_err(|e| e.to_string())? {
+        return Ok(None);
+    }
+    Ok(Some(file_name))
+}
+
+fn write_file(
+    file: &Path,
+    value: &Value,
+    src: &Path,
+    out: &Path,
+    format: &str,
+) -> Result<(), String> {
+    let content = output::serialize(&value.to_serializable(), format)
+        .map_err(|e| format!("{}: {}", file.display(), e))?;
+    let target = out
+        .join(file.strip_prefix(src).unwrap())
+        .with_extension(output::extension(format));
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("cannot create {}: {}", parent.display(), e))?;
+    }
+    fs::write(&target, content).map_err(|e| format!("cannot write {}: {}", target.display(), e))
+}
@@ -0,0 +1,96 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+use syconf_lib::{Evaluated, Session, Value};
+
+const HELP: &str = "Enter an expression to evaluate it or `let` statements to add bindings.
+Commands:
+  :type EXPR     prints the type of the value
+  :methods EXPR  lists the methods of the value
+  :load FILE     adds the top-level bindings of the file
+  :help          prints this help
+  :quit          exits";
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("repl")
+        .about("Evaluates expressions interactively")
+        .arg(
+            Arg::with_name("input")
+                .help("File to load the top-level bindings from")
+                .value_name("CONFIG_FILE"),
+        )
+        .args(&crate::evaluation_args())
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), String> {
+    let options = crate::evaluation_options(matches)?;
+    let mut session = Session::new(&options);
+    if let Some(file) = matches.value_of("input") {
+        load(&mut session, file);
+    }
+    let mut editor = DefaultEditor::new().map_err(|e| e.to_string())?;
+    loop {
+        let line = match editor.readline("> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => return Ok(()),
+            Err(e) => return Err(e.to_string()),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+        let (command, arg) = match line.strip_prefix(':') {
+            Some(command) => command
+                .split_once(char::is_whitespace)
+                .map_or((command, ""), |(c, arg)| (c, arg.trim())),
+            None => ("", line),
+        };
+        match command {
+            "" => match session.eval(arg) {
+                Ok(Evaluated::Value(value)) => println!("{}", value.to_syconf_string()),
+                Ok(Evaluated::Bindings(_)) => {}
+                Err(e) => eprintln!("ERROR: {}", e),
+            },
+            "type" => {
+                if let Some(value) = eval_value(&mut session, arg) {
+                    println!("{}", value.type_name());
+                }
+            }
+            "methods" => match eval_value(&mut session, arg) {
+                Some(value) if value.method_names().is_empty() => {
+                    println!("{} has no methods", value.type_name())
+                }
+                Some(value) => println!("{}", value.method_names().join(", ")),
+                None => {}
+            },
+            "load" => load(&mut session, arg),
+            "help" => println!("{}", HELP),
+            "quit" | "q" => return Ok(()),
+            _ => eprintln!("ERROR: unknown command ':{}', see :help", command),
+        }
+    }
+}
+
+fn load(session: &mut Session, file: &str) {
+    match session.load_file(file) {
+        Ok(names) => println!("Loaded {}", names.join(", ")),
+        Err(e) => eprintln!("ERROR: {}", e),
+    }
+}
+
+fn eval_value(session: &mut Session, expr: &str) -> Option<Value> {
+    match session.eval(expr) {
+        Ok(Evaluated::Value(value)) => Some(value),
+        Ok(Evaluated::Bindings(_)) => {
+            eprintln!("ERROR: expected an expression");
+            None
+        }
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+use clap::ArgMatches;
+use notify::{Event, RecursiveMode, Watcher};
+
+use syconf_lib::Options;
+
+// Changes that arrive within this time are handled with a single conversion.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Converts the input every time one of the files read by the previous conversion changes.
+pub fn run(matches: &ArgMatches, options: &Options) -> ! {
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(tx).unwrap_or_else(|e| {
+        eprintln!("ERROR: cannot watch files: {}", e);
+        std::process::exit(1);
+    });
+    let mut watched_dirs = HashSet::new();
+    loop {
+        let options = Options {
+            dependencies: Default::default(),
+            inputs: options.inputs.cleared(),
+            ..options.clone()
+        };
+        match crate::convert(matches, &options) {
+            Ok(()) => eprintln!("Converted {}", matches.value_of("input").unwrap()),
+            Err(e) => eprintln!("ERROR: {}", e),
+        }
+
+        let files: HashSet<PathBuf> = options.dependencies.files().into_iter().collect();
+        // Directories are watched instead of the files, because editors often replace files on save.
+        let dirs: HashSet<PathBuf> = files
+            .iter()
+            .filter_map(|x| x.parent().map(PathBuf::from))
+            .collect();
+        for dir in watched_dirs.difference(&dirs) {
+            let _ = watcher.unwatch(dir);
+        }
+        for dir in dirs.difference(&watched_dirs) {
+            if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+                eprintln!("ERROR: cannot watch {}: {}", dir.display(), e);
+            }
+        }
+        watched_dirs = dirs;
+
+        wait_for_change(&rx, &files);
+    }
+}
+
+fn wait_for_change(rx: &Receiver<notify::Result<Event>>, files: &HashSet<PathBuf>) {
+    let is_relevant = |event: notify::Result<Event>| match event {
+        Ok(event) => !event.kind.is_access() && event.paths.iter().any(|x| files.contains(x)),
+        Err(_) => false,
+    };
+    while let Ok(event) = rx.recv() {
+        if is_relevant(event) {
+            break;
+        }
+    }
+    while rx.recv_timeout(DEBOUNCE).is_ok() {}
+}
@@ -0,0 +1,223 @@
+//! Concrete syntax tree of a source. Unlike the AST of the parser it keeps comments and line breaks,
+//! but it knows only tokens and the groups formed by brackets.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Token<'a> {
+    /// Identifier, keyword or number.
+    Word(&'a str),
+    Str(&'a str),
+    Punct(&'a str),
+    /// `//` comment without the line ending.
+    Comment(&'a str),
+    BlockComment(&'a str),
+    /// Line break, `blank` if it is followed by empty lines.
+    Newline {
+        blank: bool,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node<'a> {
+    Token(Token<'a>),
+    Group { open: char, children: Vec<Node<'a>> },
+}
+
+pub const KEYWORDS: &[&str] = &[
+    "let", "in", "if", "then", "else", "assert", "try", "import", "from", "with", "export",
+    "param", "and", "or", "not",
+];
+
+const PUNCTUATION: &[&str] = &[
+    "=>", "==", "!=", ">=", "<=", "=", "+", "-", "*", "/", "<", ">", ":", ",", ".", ";",
+];
+
+impl<'a> Node<'a> {
+    pub fn is_code(&self) -> bool {
+        !matches!(
+            self,
+            Node::Token(Token::Comment(_))
+                | Node::Token(Token::BlockComment(_))
+                | Node::Token(Token::Newline { .. })
+        )
+    }
+
+    /// Returns true if the node ends an operand, i.e. a following `(` or `[` is a suffix operator.
+    pub fn is_value(&self) -> bool {
+        match self {
+            Node::Token(Token::Word(w)) => !KEYWORDS.contains(w),
+            Node::Token(Token::Str(_)) | Node::Group { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+pub fn parse(source: &str) -> Result<Vec<Node<'_>>, String> {
+    let mut stack: Vec<(char, Vec<Node>)> = vec![(' ', vec![])];
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        let len = match c {
+            ' ' | '\t' | '\r' => 1,
+            '\n' => {
+                let len = rest
+                    .find(|x: char| !x.is_whitespace())
+                    .unwrap_or(rest.len());
+                let blank = rest[..len].matches('\n').count() > 1;
+                let nodes = &mut stack.last_mut().unwrap().1;
+                nodes.push(Node::Token(Token::Newline { blank }));
+                len
+            }
+            '(' | '[' | '{' => {
+                stack.push((c, vec![]));
+                1
+            }
+            ')' | ']' | '}' => {
+                let (open, children) = stack.pop().unwrap();
+                if open == ' ' || closing(open) != c {
+                    return Err(format!("unexpected '{}'", c));
+                }
+                stack
+                    .last_mut()
+                    .unwrap()
+                    .1
+                    .push(Node::Group { open, children });
+                1
+            }
+            _ => {
+                let nodes = &mut stack.last_mut().unwrap().1;
+                let after_value = nodes
+                    .iter()
+                    .rev()
+                    .find(|x| x.is_code())
+                    .is_some_and(Node::is_value);
+                let (token, len) = token(rest, after_value)?;
+                nodes.push(Node::Token(token));
+                len
+            }
+        };
+        rest = &rest[len..];
+    }
+    match stack.pop() {
+        Some((' ', nodes)) => Ok(nodes),
+        Some((open, _)) => Err(format!("'{}' is not closed", open)),
+        None => unreachable!(),
+    }
+}
+
+pub fn closing(open: char) -> char {
+    match open {
+        '(' => ')',
+        '[' => ']',
+        '{' => '}',
+        _ => unreachable!(),
+    }
+}
+
+fn token(rest: &str, after_value: bool) -> Result<(Token<'_>, usize), String> {
+    let is_word_char = |x: char| x.is_ascii_alphanumeric() || x == '_';
+    let bytes = rest.as_bytes();
+    let len = if rest.starts_with("//") {
+        let len = rest.find('\n').unwrap_or(rest.len());
+        return Ok((Token::Comment(rest[..len].trim_end()), len));
+    } else if rest.starts_with("/*") {
+        let len = block_comment_len(rest).ok_or("block comment is not closed")?;
+        return Ok((Token::BlockComment(&rest[..len]), len));
+    } else if let Some(body) = rest.strip_prefix("\"\"\"") {
+        body.find("\"\"\"").ok_or("string is not closed")? + 6
+    } else if bytes[0] == b'"' || bytes[0] == b'\'' || bytes[0] == b'#' {
+        let hashes = rest.find(|x| x != '#').unwrap_or(rest.len());
+        let quote = rest[hashes..]
+            .chars()
+            .next()
+            .filter(|x| *x == '"' || *x == '\'')
+            .ok_or("unexpected '#'")?;
+        let end = format!("{}{}", quote, &rest[..hashes]);
+        let body = hashes + 1;
+        body + rest[body..].find(&end).ok_or("string is not closed")? + end.len()
+    } else if bytes[0].is_ascii_digit()
+        || (!after_value
+            && (bytes[0] == b'-' || bytes[0] == b'+')
+            && bytes.get(1).is_some_and(u8::is_ascii_digit))
+    {
+        let digits = |from: usize| {
+            from + rest[from..]
+                .find(|x: char| !x.is_ascii_digit())
+                .unwrap_or(rest.len() - from)
+        };
+        let len = digits(1);
+        if rest[len..].starts_with('.') {
+            digits(len + 1)
+        } else {
+            len
+        }
+    } else if is_word_char(rest.chars().next().unwrap()) {
+        let len = rest.find(|x| !is_word_char(x)).unwrap_or(rest.len());
+        return Ok((Token::Word(&rest[..len]), len));
+    } else {
+        let punct = PUNCTUATION
+            .iter()
+            .find(|x| rest.starts_with(*x))
+            .ok_or_else(|| format!("unexpected '{}'", rest.chars().next().unwrap()))?;
+        return Ok((Token::Punct(punct), punct.len()));
+    };
+    let token = &rest[..len];
+    Ok((
+        if token.starts_with(|x: char| x.is_ascii_digit() || x == '-' || x == '+') {
+            Token::Word(token)
+        } else {
+            Token::Str(token)
+        },
+        len,
+    ))
+}
+
+fn block_comment_len(rest: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut ix = 0;
+    while ix < rest.len() {
+        if rest[ix..].starts_with("/*") {
+            depth += 1;
+            ix += 2;
+        } else if rest[ix..].starts_with("*/") {
+            depth -= 1;
+            ix += 2;
+            if depth == 0 {
+                return Some(ix);
+            }
+        } else {
+            ix += rest[ix..].chars().next().unwrap().len_utf8();
+        }
+    }
+    None
+}
+
+#[test]
+fn tokens() {
+    let nodes = parse("let a = -1 - 2.5 // x\n\n\n{ b: f(\"s\") }").unwrap();
+    assert_eq!(
+        nodes,
+        vec![
+            Node::Token(Token::Word("let")),
+            Node::Token(Token::Word("a")),
+            Node::Token(Token::Punct("=")),
+            Node::Token(Token::Word("-1")),
+            Node::Token(Token::Punct("-")),
+            Node::Token(Token::Word("2.5")),
+            Node::Token(Token::Comment("// x")),
+            Node::Token(Token::Newline { blank: true }),
+            Node::Group {
+                open: '{',
+                children: vec![
+                    Node::Token(Token::Word("b")),
+                    Node::Token(Token::Punct(":")),
+                    Node::Token(Token::Word("f")),
+                    Node::Group {
+                        open: '(',
+                        children: vec![Node::Token(Token::Str("\"s\""))]
+                    },
+                ]
+            },
+        ]
+    );
+    assert_eq!(parse("(]").unwrap_err(), "unexpected ']'");
+    assert_eq!(parse("[ /* a */").unwrap_err(), "'[' is not closed");
+}
@@ -0,0 +1,117 @@
+//! Formatter that re-emits a source with consistent layout and keeps its comments.
+//!
+//! Spacing and indentation are normalized, line breaks between statements are kept.
+//! A group in brackets stays on one line if it is written on one line,
+//! otherwise every item goes on its own line and gets a trailing comma.
+
+use crate::parser::{parse_complete_unit, Span};
+use crate::resolver::ErrorWithLocation;
+
+mod cst;
+mod printer;
+
+/// Formats a source, the source name is used in error messages.
+pub fn format_source(source: &str, source_name: &str) -> Result<String, ErrorWithLocation> {
+    let expr = parse_complete_unit(Span::new_extra(source, source_name.into()))?;
+    let nodes = cst::parse(source).map_err(|e| format!("{}: {}", source_name, e))?;
+    let formatted = printer::print(&nodes);
+    // The formatter works on tokens only, make sure the layout did not change the meaning.
+    let formatted_expr = parse_complete_unit(Span::new_extra(&formatted, source_name.into()))
+        .map_err(|e| {
+            format!(
+                "{}: formatting produced an invalid source: {}",
+                source_name, e.message
+            )
+        })?;
+    if expr != formatted_expr {
+        return Err(format!(
+            "{}: formatting changed the meaning of the source",
+            source_name
+        )
+        .into());
+    }
+    Ok(formatted)
+}
+
+#[test]
+fn layout_independent_trees() {
+    let tree = |source| parse_complete_unit(Span::new_extra(source, "<input>".into())).unwrap();
+    assert_eq!(tree("f( 1,\n'{a}' )"), tree("f(1, '{a}')"));
+    assert_ne!(tree("f(1, '{a}')"), tree("f(1, '{b}')"));
+    assert_ne!(tree("1 + 2 * 3"), tree("(1 + 2) * 3"));
+}
+
+#[cfg(test)]
+fn check(source: &str, expected: &str) {
+    let formatted = format_source(source, "<input>").unwrap();
+    assert_eq!(formatted, expected);
+    assert_eq!(format_source(&formatted, "<input>").unwrap(), formatted);
+}
+
+#[test]
+fn spacing() {
+    check(
+        "let a=f( 1 ,2 )[0].b\nlet c = -1-2 * (a+1)\nin {x:a,y:[ c,3, ],z:{ }}",
+        "let a = f(1, 2)[0].b\nlet c = -1 - 2 * (a + 1)\nin { x: a, y: [c, 3], z: {} }\n",
+    );
+    check(
+        "assert a==1:\"msg\" in not ( a )",
+        "assert a == 1 : \"msg\" in not (a)\n",
+    );
+}
+
+#[test]
+fn multiline_groups() {
+    check(
+        r#"let m = {
+  // first
+  a: 1 // one
+  b: [1,
+    2]
+
+      c: (x) =>
+    x
+}
+in
+m"#,
+        r#"let m = {
+    // first
+    a: 1, // one
+    b: [
+        1,
+        2,
+    ],
+
+    c: (x) =>
+        x,
+}
+in
+m
+"#,
+    );
+    check("f(\n1,\n2,\n)", "f(\n    1,\n    2\n)\n");
+}
+
+#[test]
+fn blocks_and_strings() {
+    check(
+        "{\nlet a = \"\"\"\n  keep\n    me\n  \"\"\"\n/* block */ in a.trim()\n}",
+        "{\n    let a = \"\"\"\n  keep\n    me\n  \"\"\"\n    /* block */ in a.trim()\n}\n",
+    );
+    check(
+        "let list = [1]\n\t.map((x) => x)\n\n\n\t.filter((x) => true)\nin list",
+        "let list = [1]\n    .map((x) => x)\n\n    .filter((x) => true)\nin list\n",
+    );
+}
+
+#[test]
+fn repository_scripts() {
+    for source in &[
+        include_str!("../../../scripts/lib/build-job.sy"),
+        include_str!("../../../scripts/lib/create-release-job.sy"),
+        include_str!("../../../scripts/github-release-workflow.sy"),
+    ] {
+        let formatted = format_source(source, "<input>").unwrap();
+        assert_eq!(format_source(&formatted, "<input>").unwrap(), formatted);
+    }
+}
@@ -0,0 +1,236 @@
+use super::cst::{closing, Node, Token};
+
+const INDENT: &str = "    ";
+
+// Tokens that continue the expression of the previous line if a line starts with them.
+const CONTINUING: &[&str] = &[
+    ".", "=>", "==", "!=", ">=", "<=", "=", "+", "-", "*", "/", "<", ">", ":", "then", "else",
+    "and", "or",
+];
+
+// Tokens that are continued on the next line if a line ends with them.
+const CONTINUED: &[&str] = &[
+    ".", "=>", "==", "!=", ">=", "<=", "=", "+", "-", "*", "/", "<", ">", ":", "if", "then",
+    "else", "assert", "try", "not", "and", "or", "with", "import", "from",
+];
+
+pub fn print(nodes: &[Node]) -> String {
+    let mut out = String::new();
+    print_sequence(&mut out, nodes, 0);
+    out.push('\n');
+    out
+}
+
+/// Prints the lines of statements or of a single item, lines that continue the previous line are indented.
+fn print_sequence(out: &mut String, nodes: &[Node], level: usize) {
+    let mut previous: Option<&[Node]> = None;
+    for (ix, (blank, line)) in lines(nodes).into_iter().enumerate() {
+        if ix > 0 {
+            out.push('\n');
+            if blank {
+                out.push('\n');
+            }
+        }
+        let continuation = previous.is_some_and(|previous| {
+            matches!(previous.iter().rev().find(|x| x.is_code()), Some(Node::Token(t)) if CONTINUED.contains(&text(t)))
+                || matches!(line.first(), Some(Node::Token(t)) if CONTINUING.contains(&text(t)))
+        });
+        let level = level + continuation as usize;
+        out.push_str(&INDENT.repeat(level));
+        print_line(out, line, level);
+        if line.iter().any(Node::is_code) {
+            previous = Some(line);
+        }
+    }
+}
+
+// Splits the nodes at line breaks and drops empty lines. The flag tells if the line follows a blank line.
+fn lines<'n, 'a>(nodes: &'n [Node<'a>]) -> Vec<(bool, &'n [Node<'a>])> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut blank = false;
+    for (ix, node) in nodes.iter().enumerate() {
+        if let Node::Token(Token::Newline { blank: blank_lines }) = node {
+            if start < ix {
+                out.push((blank, &nodes[start..ix]));
+                blank = false;
+            }
+            blank |= *blank_lines;
+            start = ix + 1;
+        }
+    }
+    if start < nodes.len() {
+        out.push((blank, &nodes[start..]));
+    }
+    out
+}
+
+fn print_line(out: &mut String, line: &[Node], level: usize) {
+    let mut asserts = 0;
+    let mut previous: Option<&Node> = None;
+    for node in line {
+        if let Some(previous) = previous {
+            out.push_str(space(previous, node, &mut asserts));
+        }
+        match node {
+            Node::Token(t) => {
+                if *t == Token::Word("assert") {
+                    asserts += 1;
+                }
+                out.push_str(text(t))
+            }
+            Node::Group { open, children } => print_group(out, *open, children, level),
+        }
+        previous = Some(node);
+    }
+}
+
+fn space(previous: &Node, next: &Node, asserts: &mut usize) -> &'static str {
+    match (previous, next) {
+        (_, Node::Token(Token::Punct(",")))
+        | (_, Node::Token(Token::Punct(";")))
+        | (Node::Token(Token::Punct(".")), _)
+        | (_, Node::Token(Token::Punct("."))) => "",
+        // The message of an assertion is separated with ` : `.
+        (_, Node::Token(Token::Punct(":"))) if *asserts > 0 => {
+            *asserts -= 1;
+            " "
+        }
+        (_, Node::Token(Token::Punct(":"))) => "",
+        (previous, Node::Group { open, .. }) if *open != '{' && previous.is_value() => "",
+        _ => " ",
+    }
+}
+
+fn text<'a>(token: &Token<'a>) -> &'a str {
+    match token {
+        Token::Word(x)
+        | Token::Str(x)
+        | Token::Punct(x)
+        | Token::Comment(x)
+        | Token::BlockComment(x) => x,
+        Token::Newline { .. } => "\n",
+    }
+}
+
+/// Prints a group on a single line if it has no line breaks, otherwise every item goes on its own line.
+fn print_group(out: &mut String, open: char, children: &[Node], level: usize) {
+    let multiline = children
+        .iter()
+        .any(|x| matches!(x, Node::Token(Token::Newline { .. })));
+    if !multiline {
+        let children = match children.last() {
+            Some(Node::Token(Token::Punct(","))) => &children[..children.len() - 1],
+            _ => children,
+        };
+        let padding = if open == '{' && !children.is_empty() {
+            " "
+        } else {
+            ""
+        };
+        out.push(open);
+        out.push_str(padding);
+        print_line(out, children, level);
+        out.push_str(padding);
+        out.push(closing(open));
+        return;
+    }
+
+    out.push(open);
+    if is_block(open, children) {
+        out.push('\n');
+        print_sequence(out, children, level + 1);
+    } else {
+        for (ix, (blank, item)) in items(open, children).into_iter().enumerate() {
+            out.push('\n');
+            if blank && ix > 0 {
+                out.push('\n');
+            }
+            print_sequence(out, &item, level + 1);
+        }
+    }
+    out.push('\n');
+    out.push_str(&INDENT.repeat(level));
+    out.push(closing(open));
+}
+
+fn is_block(open: char, children: &[Node]) -> bool {
+    let mut code = children.iter().filter(|x| x.is_code());
+    open == '{'
+        && match code.next() {
+            Some(Node::Token(Token::Word("let"))) | Some(Node::Token(Token::Word("param"))) => true,
+            Some(Node::Token(Token::Word("import"))) => {
+                matches!(code.next(), Some(Node::Group { open: '{', .. }))
+            }
+            _ => false,
+        }
+}
+
+/// Splits the content of a group into items separated by commas, or by line breaks in hashmaps.
+/// Every item gets a trailing comma, except the last one in parentheses.
+/// The flag tells if the item follows a blank line.
+fn items<'a>(open: char, children: &[Node<'a>]) -> Vec<(bool, Vec<Node<'a>>)> {
+    let mut items: Vec<Vec<Node>> = vec![vec![]];
+    let mut ix = 0;
+    while ix < children.len() {
+        let current = items.last_mut().unwrap();
+        match &children[ix] {
+            Node::Token(Token::Punct(",")) => {
+                // Comments on the same line belong to the finished item.
+                while let Some(comment) = children.get(ix + 1).filter(|x| {
+                    matches!(
+                        x,
+                        Node::Token(Token::Comment(_)) | Node::Token(Token::BlockComment(_))
+                    )
+                }) {
+                    current.push(comment.clone());
+                    ix += 1;
+                }
+                items.push(vec![]);
+            }
+            newline @ Node::Token(Token::Newline { .. })
+                if open == '{'
+                    && current.iter().any(Node::is_code)
+                    && starts_entry(&children[ix..]) =>
+            {
+                items.push(vec![newline.clone()]);
+            }
+            node => current.push(node.clone()),
+        }
+        ix += 1;
+    }
+
+    items.retain(|x| {
+        x.iter()
+            .any(|x| !matches!(x, Node::Token(Token::Newline { .. })))
+    });
+    let last_code_item = items.iter().rposition(|x| x.iter().any(Node::is_code));
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(ix, mut item)| {
+            let blank = matches!(
+                item.first(),
+                Some(Node::Token(Token::Newline { blank: true }))
+            );
+            if let Some(last) = item.iter().rposition(Node::is_code) {
+                if open != '(' || Some(ix) != last_code_item {
+                    item.insert(last + 1, Node::Token(Token::Punct(",")));
+                }
+            }
+            (blank, item)
+        })
+        .collect()
+}
+
+// Returns true if the first code line after the line break starts with `key:`.
+fn starts_entry(nodes: &[Node]) -> bool {
+    let mut code = nodes.iter().filter(|x| x.is_code());
+    matches!(
+        (code.next(), code.next()),
+        (
+            Some(Node::Token(Token::Word(_))) | Some(Node::Token(Token::Str(_))),
+            Some(Node::Token(Token::Punct(":")))
+        )
+    )
+}
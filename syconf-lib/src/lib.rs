@@ -5,42 +5,209 @@ extern crate derivative;
 #[macro_use]
 extern crate tracing;
 
+use std::collections::HashMap;
 use std::fs::read_to_string;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use parser::*;
+use resolver::Evaluator;
 
-pub use crate::resolver::ErrorWithLocation;
+pub use crate::resolver::{
+    diff, Change, Dependencies, ErrorWithLocation, Evaluated, Inputs, InputsReport, Session,
+};
 pub use crate::resolver::{Func, SerializableValue, TypeMismatch, Value, ValueString};
 pub use parser::Number;
 
+mod formatter;
 mod parser;
 mod resolver;
 
 #[cfg(test)]
 mod tests;
 
+/// Options that control the evaluation.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// Directories to look up imports in, unless the imported path is explicitly relative,
+    /// i.e. starts with `./` or `../`.
+    pub lib_dirs: Vec<PathBuf>,
+
+    /// Arguments to call the configuration with, if it evaluates to a function.
+    /// The arguments are matched with the function parameters by name.
+    pub args: Option<HashMap<String, Value>>,
+
+    /// External variables that are accessible with the `ext` function.
+    pub ext_vars: HashMap<String, Value>,
+
+    /// Collects the files read during the evaluation, e.g. to re-evaluate when one of them changes.
+    pub dependencies: Dependencies,
+
+    /// Records the environment variables, files and shell commands the evaluation consumed.
+    /// Use `Inputs::frozen` to fail the evaluation if an input differs from a previous report.
+    pub inputs: Inputs,
+}
+
+impl Options {
+    /// Creates options with the import search path taken from the `SYCONF_PATH` environment variable.
+    pub fn from_env() -> Self {
+        Self {
+            lib_dirs: std::env::var_os("SYCONF_PATH")
+                .map(|x| std::env::split_paths(&x).collect())
+                .unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Formats a source with consistent layout, comments are kept.
+pub fn format_string(input: &str) -> Result<String, ErrorWithLocation> {
+    formatter::format_source(input, "<input>")
+}
+
+/// Returns the formatted content of the file.
+pub fn format_file(file_name: &str) -> Result<String, ErrorWithLocation> {
+    let content = read_to_string(file_name).map_err(|e| ErrorWithLocation {
+        location: None,
+        message: format!("Cannot read file '{}': {}", file_name, e),
+    })?;
+    formatter::format_source(&content, file_name)
+}
+
 pub fn parse_string(input: &str) -> Result<Value, ErrorWithLocation> {
-    parse_source(Span::new_extra(input, "<input>".into()))
+    parse_string_with_options(input, &Options::default())
+}
+
+pub fn parse_string_with_options(
+    input: &str,
+    options: &Options,
+) -> Result<Value, ErrorWithLocation> {
+    parse_source(
+        Span::new_extra(input, "<input>".into()),
+        options,
+        &Evaluator::new(options),
+    )
 }
 
 pub fn parse_file(file_name: &str) -> Result<Value, ErrorWithLocation> {
+    parse_file_with_options(file_name, &Options::default())
+}
+
+pub fn parse_file_with_options(
+    file_name: &str,
+    options: &Options,
+) -> Result<Value, ErrorWithLocation> {
+    evaluate_file(file_name, options, &Evaluator::new(options))
+}
+
+/// Parses several files with the same options. Imports shared by the files are parsed only once.
+pub fn parse_files_with_options(
+    file_names: &[&str],
+    options: &Options,
+) -> Vec<Result<Value, ErrorWithLocation>> {
+    let evaluator = Evaluator::new(options);
+    file_names
+        .iter()
+        .map(|file_name| evaluate_file(file_name, options, &evaluator))
+        .collect()
+}
+
+fn evaluate_file(
+    file_name: &str,
+    options: &Options,
+    evaluator: &Evaluator,
+) -> Result<Value, ErrorWithLocation> {
     let content = read_to_string(file_name).map_err(|e| ErrorWithLocation {
         location: None,
         message: format!("Cannot read file '{}': {}", file_name, e),
     })?;
-    let normalized_fn = std::fs::canonicalize(Path::new(file_name))
-        .unwrap()
-        .to_str()
-        .unwrap()
-        .to_owned();
-    parse_source(Span::new_extra(&content, normalized_fn.into()))
-}
-
-fn parse_source(source: Span) -> Result<Value, ErrorWithLocation> {
-    let (rest, expr) = parse_unit(source)?;
-    if !rest.fragment().is_empty() {
-        return Err(anyhow!("Cannot parse (incomplete): '{}'", rest.fragment()).into());
+    let normalized_fn =
+        std::fs::canonicalize(Path::new(file_name)).map_err(|e| ErrorWithLocation {
+            location: None,
+            message: format!("Cannot read file '{}': {}", file_name, e),
+        })?;
+    options.dependencies.add_file(normalized_fn.clone());
+    if let Err(e) = options.inputs.file(&normalized_fn, Some(&content)) {
+        options.inputs.take_violation();
+        return Err(e.into());
+    }
+    parse_source(
+        Span::new_extra(&content, normalized_fn.to_string_lossy().into()),
+        options,
+        evaluator,
+    )
+}
+
+/// Returns true if the file is meant to be imported rather than evaluated on its own,
+/// i.e. it is a module, declares parameters or evaluates to a function.
+pub fn is_library_file(file_name: &str) -> Result<bool, ErrorWithLocation> {
+    let content = read_to_string(file_name).map_err(|e| ErrorWithLocation {
+        location: None,
+        message: format!("Cannot read file '{}': {}", file_name, e),
+    })?;
+    let expr = parse_complete_unit(Span::new_extra(&content, file_name.into()))?;
+    Ok(match &expr.inner {
+        Expr::Module(_) | Expr::FuncDefinition(_) => true,
+        Expr::Block(block) => {
+            matches!(block.expression.inner, Expr::FuncDefinition(_))
+                || block
+                    .local_assignments
+                    .iter()
+                    .any(|x| matches!(x.1.inner, Expr::Param(..)))
+        }
+        _ => false,
+    })
+}
+
+/// Parses a file that evaluates to a function and calls it with the given arguments.
+pub fn parse_file_with_args(
+    file_name: &str,
+    args: HashMap<String, Value>,
+) -> Result<Value, ErrorWithLocation> {
+    let options = Options {
+        args: Some(args),
+        ..Default::default()
+    };
+    parse_file_with_options(file_name, &options)
+}
+
+fn parse_source(
+    source: Span,
+    options: &Options,
+    evaluator: &Evaluator,
+) -> Result<Value, ErrorWithLocation> {
+    let value = evaluator.resolve(&parse_complete_unit(source)?);
+    let value = match (value, &options.args) {
+        (Ok(value), Some(args)) => call_with_args(value, args),
+        (value, _) => value,
+    };
+    // An input that differs from the frozen inputs fails the evaluation, even if it recovered from the error.
+    let checked = evaluator.check_inputs();
+    value.and_then(|value| checked.map(|_| value))
+}
+
+fn call_with_args(value: Value, args: &HashMap<String, Value>) -> Result<Value, ErrorWithLocation> {
+    let func = match value {
+        Value::Func(func) => func,
+        value if args.is_empty() => return Ok(value),
+        _ => return Err("Arguments are given, but the configuration is not a function".into()),
+    };
+    let names = func
+        .argument_names()
+        .ok_or("Configuration function does not have named arguments")?;
+    if let Some(unknown) = args.keys().find(|x| !names.contains(x)) {
+        return Err(format!(
+            "Configuration function does not take argument '{}'",
+            unknown
+        )
+        .into());
     }
-    resolver::resolve(&expr)
+    let values = names
+        .iter()
+        .map(|name| {
+            args.get(name)
+                .cloned()
+                .ok_or_else(|| format!("Argument '{}' is not set", name).into())
+        })
+        .collect::<Result<Vec<Value>, ErrorWithLocation>>()?;
+    func.call(&values)
 }
@@ -0,0 +1,31 @@
+use nom::bytes::complete::tag;
+use nom::combinator::{cut, map, opt};
+use nom::sequence::{pair, preceded, tuple};
+use nom::IResult;
+
+use crate::parser::expr::expr;
+use crate::parser::{ml_space0, ml_space1, ExprWithLocation, Span};
+
+#[derive(Debug, PartialEq)]
+pub struct Assertion<'a> {
+    pub condition: ExprWithLocation<'a>,
+    pub message: Option<ExprWithLocation<'a>>,
+    pub expression: ExprWithLocation<'a>,
+}
+
+pub fn assertion(input: Span) -> IResult<Span, Assertion> {
+    map(
+        tuple((
+            pair(tag("assert"), ml_space1),
+            cut(expr),
+            opt(preceded(tuple((ml_space0, tag(":"), ml_space0)), cut(expr))),
+            cut(tuple((ml_space1, tag("in"), ml_space1))),
+            cut(expr),
+        )),
+        |(_, condition, message, _, expression)| Assertion {
+            condition,
+            message,
+            expression,
+        },
+    )(input)
+}
@@ -1,13 +1,15 @@
 use nom::bytes::complete::*;
 use nom::combinator::{map, opt};
-use nom::multi::separated_list1;
-use nom::sequence::{delimited, pair, tuple};
+use nom::multi::{many0, separated_list1};
+use nom::sequence::{pair, preceded, terminated, tuple};
 use nom::IResult;
+use nom_locate::position;
 
 use super::*;
 
+/// A `let` binding with its optional `///` doc comment.
 #[derive(Debug, PartialEq)]
-pub struct Assignment<'a>(pub &'a str, pub ExprWithLocation<'a>);
+pub struct Assignment<'a>(pub &'a str, pub ExprWithLocation<'a>, pub Option<String>);
 
 fn assignment(input: Span) -> IResult<Span, Assignment> {
     map(
@@ -18,10 +20,92 @@ fn assignment(input: Span) -> IResult<Span, Assignment> {
             expr,
             opt(pair(ml_space0, tag(";"))),
         )),
-        |(_, ident, _, ex, _)| Assignment(ident, ex),
+        |(_, ident, _, ex, _)| Assignment(ident, ex, None),
     )(input)
 }
 
+/// Parses `param name = default`, a parameter that can be set by an importer.
+fn param(input: Span) -> IResult<Span, Assignment> {
+    map(
+        tuple((
+            position,
+            pair(tag("param"), ml_space1),
+            identifier,
+            opt(preceded(tuple((ml_space0, tag("="), ml_space0)), cut(expr))),
+            opt(pair(ml_space0, tag(";"))),
+        )),
+        |(pos, _, ident, default, _)| {
+            Assignment(
+                ident,
+                Expr::Param(ident, default.map(Box::new)).with_location(pos),
+                None,
+            )
+        },
+    )(input)
+}
+
+/// Parses `import { a, b } from "./file.sy"` into an assignment per imported name.
+fn selective_import(input: Span) -> IResult<Span, Vec<Assignment>> {
+    map(
+        tuple((
+            tuple((tag("import"), ml_space0, tag("{"), ml_space0)),
+            separated_list1(
+                tuple((ml_space0, tag(","), ml_space0)),
+                pair(position, identifier),
+            ),
+            tuple((
+                opt(pair(ml_space0, tag(","))),
+                ml_space0,
+                tag("}"),
+                ml_space1,
+                tag("from"),
+                ml_space1,
+                tag("\""),
+            )),
+            is_not("\""),
+            tag("\""),
+            opt(pair(ml_space0, tag(";"))),
+        )),
+        |(_, names, _, path, _, _)| {
+            names
+                .into_iter()
+                .map(|(pos, name)| {
+                    Assignment(
+                        name,
+                        Expr::SelectiveImport(path.fragment(), name).with_location(pos),
+                        None,
+                    )
+                })
+                .collect()
+        },
+    )(input)
+}
+
+fn statement(input: Span) -> IResult<Span, Vec<Assignment>> {
+    alt((
+        map(assignment, |x| vec![x]),
+        map(param, |x| vec![x]),
+        selective_import,
+    ))(input)
+}
+
+fn documented<'a, O>(
+    space: impl FnMut(Span<'a>) -> IResult<Span<'a>, &'a str>,
+    statement: impl FnMut(Span<'a>) -> IResult<Span<'a>, (O, Vec<Assignment<'a>>)>,
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, (O, Vec<Assignment<'a>>)> {
+    map(pair(space, statement), |(space, (o, mut list))| {
+        let doc = doc_comment(space);
+        for a in &mut list {
+            a.2 = doc.clone();
+        }
+        (o, list)
+    })
+}
+
+fn unit_statement(input: Span) -> IResult<Span, ((), Vec<Assignment>)> {
+    map(statement, |x| ((), x))(input)
+}
+
 #[derive(Debug, PartialEq)]
 pub struct BlockExpr<'a> {
     pub local_assignments: Vec<Assignment<'a>>,
@@ -30,19 +114,24 @@ pub struct BlockExpr<'a> {
 
 pub fn block_body(input: Span) -> IResult<Span, BlockExpr> {
     map(
-        delimited(
-            ml_space0,
+        terminated(
             pair(
                 pair(
-                    separated_list1(ml_space1, assignment),
+                    pair(
+                        documented(ml_space0, unit_statement),
+                        many0(documented(ml_space1, unit_statement)),
+                    ),
                     cut(tuple((ml_space1, tag("in"), ml_space1))),
                 ),
                 expr,
             ),
             ml_space0,
         ),
-        |(la, expression)| BlockExpr {
-            local_assignments: la.0,
+        |(((first, rest), _), expression)| BlockExpr {
+            local_assignments: std::iter::once(first)
+                .chain(rest)
+                .flat_map(|x| x.1)
+                .collect(),
             expression: Box::new(expression),
         },
     )(input)
@@ -51,3 +140,97 @@ pub fn block_body(input: Span) -> IResult<Span, BlockExpr> {
 pub fn block_expr(input: Span) -> IResult<Span, BlockExpr> {
     delimited(tag("{"), block_body, tag("}"))(input)
 }
+
+/// A file with top-level `let` bindings some of which are exported with `export let`.
+#[derive(Debug, PartialEq)]
+pub struct Module<'a> {
+    pub assignments: Vec<Assignment<'a>>,
+    pub exports: Vec<&'a str>,
+}
+
+impl Module<'_> {
+    /// A file is only a module if it exports or selectively imports bindings,
+    /// otherwise its `let` bindings must be followed by `in`.
+    pub fn has_imports_or_exports(&self) -> bool {
+        !self.exports.is_empty()
+            || self
+                .assignments
+                .iter()
+                .any(|x| matches!(x.1.inner, Expr::SelectiveImport(..)))
+    }
+}
+
+fn module_statement(input: Span) -> IResult<Span, (bool, Vec<Assignment>)> {
+    alt((
+        map(
+            pair(pair(tag("export"), ml_space1), assignment),
+            |(_, a)| (true, vec![a]),
+        ),
+        map(statement, |x| (false, x)),
+    ))(input)
+}
+
+pub fn module_body(input: Span) -> IResult<Span, Module> {
+    map(
+        terminated(
+            pair(
+                documented(ml_space0, module_statement),
+                many0(documented(ml_space1, module_statement)),
+            ),
+            ml_space0,
+        ),
+        |(first, rest)| {
+            let mut module = Module {
+                assignments: vec![],
+                exports: vec![],
+            };
+            for (exported, assignments) in std::iter::once(first).chain(rest) {
+                for a in assignments {
+                    if exported {
+                        module.exports.push(a.0);
+                    }
+                    module.assignments.push(a);
+                }
+            }
+            module
+        },
+    )(input)
+}
+
+#[test]
+fn assignment_doc_comments() {
+    use crate::parser::test_helpers::span;
+    let block = block_body(span(
+        r#"
+    /// The answer.
+    let a = 42
+    // not a doc comment
+    let b = a
+    in b"#,
+    ))
+    .unwrap()
+    .1;
+    assert_eq!(
+        block.local_assignments[0].2,
+        Some("The answer.".to_string())
+    );
+    assert_eq!(block.local_assignments[1].2, None);
+}
+
+#[test]
+fn module() {
+    use crate::parser::test_helpers::span;
+    let module = module_body(span(
+        r#"
+    import { a, b } from "./lib.sy"
+    let helper = a
+    export let deployment = helper
+    export let service = b
+    "#,
+    ))
+    .unwrap()
+    .1;
+    let names: Vec<&str> = module.assignments.iter().map(|x| x.0).collect();
+    assert_eq!(names, vec!["a", "b", "helper", "deployment", "service"]);
+    assert_eq!(module.exports, vec!["deployment", "service"]);
+}
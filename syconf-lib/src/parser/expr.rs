@@ -4,18 +4,28 @@ use nom::bytes::complete::*;
 use nom::sequence::pair;
 use nom::{AsChar, IResult, InputLength, InputTake};
 
-use crate::parser::block::BlockExpr;
+use crate::parser::assertion::Assertion;
+use crate::parser::block::{BlockExpr, Module};
 use crate::parser::comparison::Comparison;
 use crate::parser::conditional::Conditional;
+use crate::parser::try_expr::TryExpr;
 
 use super::*;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 pub struct ExprWithLocation<'a> {
     pub inner: Expr<'a>,
     pub location: Span<'a>,
 }
 
+/// Expressions are equal if they have the same structure, regardless of their locations,
+/// so that syntax trees of sources which differ only in layout compare equal.
+impl PartialEq for ExprWithLocation<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
 // TODO: add special characters in strings, i.e. \n, \t
 // TODO: add float type
 #[derive(Debug, PartialEq)]
@@ -27,9 +37,17 @@ pub enum Expr<'a> {
     BinaryOperator(Box<BinaryOperatorExpr<'a>>),
     Comparison(Box<Comparison<'a>>),
     Conditional(Box<Conditional<'a>>),
+    Assertion(Box<Assertion<'a>>),
+    Try(Box<TryExpr<'a>>),
     Logical(Box<Logical<'a>>),
     Suffix(Box<SuffixExpr<'a>>),
-    Import(&'a str),
+    Import(&'a str, Vec<HashMapEntry<'a>>),
+    // An exported name of an imported file: (path, name).
+    SelectiveImport(&'a str, &'a str),
+    ImportGlob(&'a str),
+    Module(Module<'a>),
+    // A parameter of the file with its optional default value.
+    Param(&'a str, Option<Box<ExprWithLocation<'a>>>),
 }
 
 impl<'a> Expr<'a> {
@@ -45,7 +63,7 @@ pub fn expr(input: Span) -> IResult<Span, ExprWithLocation> {
     expr_logical(input)
 }
 
-pub fn identifier(input: Span) -> IResult<Span, &str> {
+pub fn identifier(input: Span<'_>) -> IResult<Span<'_>, &str> {
     let (next_input, (a, b)) = pair(
         take_while1(|x: char| x.is_alpha() || x == '_'),
         take_while(|x: char| x.is_alphanumeric() || x == '_'),
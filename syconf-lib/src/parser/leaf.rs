@@ -2,12 +2,14 @@ use std::rc::Rc;
 
 use nom::branch::alt;
 use nom::bytes::complete::*;
-use nom::combinator::map;
-use nom::sequence::{delimited, pair, tuple};
+use nom::combinator::{map, opt};
+use nom::sequence::{delimited, pair, preceded, tuple};
 use nom::IResult;
 
+use crate::parser::assertion::assertion;
 use crate::parser::block::block_expr;
 use crate::parser::conditional::*;
+use crate::parser::try_expr::try_expr;
 
 use super::*;
 use nom_locate::position;
@@ -15,9 +17,16 @@ use nom_locate::position;
 pub fn expr_leaf(input: Span) -> IResult<Span, ExprWithLocation> {
     alt((
         import,
+        import_glob,
         map(pair(position, conditional), |(pos, x)| {
             Expr::Conditional(Box::new(x)).with_location(pos)
         }),
+        map(pair(position, assertion), |(pos, x)| {
+            Expr::Assertion(Box::new(x)).with_location(pos)
+        }),
+        map(pair(position, try_expr), |(pos, x)| {
+            Expr::Try(Box::new(x)).with_location(pos)
+        }),
         map(pair(position, config_value), |(pos, x)| {
             Expr::Value(x).with_location(pos)
         }),
@@ -42,7 +51,31 @@ fn import(input: Span) -> IResult<Span, ExprWithLocation> {
             tuple((tag("import"), ml_space1, tag("\""))),
             is_not("\""),
             tag("\""),
+            opt(preceded(
+                tuple((ml_space1, tag("with"), ml_space1)),
+                cut(hashmap),
+            )),
+        )),
+        |(pos, _, path, _, params)| {
+            Expr::Import(path.fragment(), params.unwrap_or_default()).with_location(pos)
+        },
+    )(input)
+}
+
+fn import_glob(input: Span) -> IResult<Span, ExprWithLocation> {
+    map(
+        tuple((
+            position,
+            tuple((
+                tag("import_glob"),
+                ml_space0,
+                tag("("),
+                ml_space0,
+                tag("\""),
+            )),
+            cut(is_not("\"")),
+            cut(tuple((tag("\""), ml_space0, tag(")")))),
         )),
-        |(pos, _, path, _)| Expr::Import(path.fragment()).with_location(pos),
+        |(pos, _, pattern, _)| Expr::ImportGlob(pattern.fragment()).with_location(pos),
     )(input)
 }
@@ -1,11 +1,12 @@
 use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::combinator::{all_consuming, cut, map, peek};
-use nom::sequence::{delimited, pair, preceded, tuple};
+use nom::combinator::{all_consuming, cut, map, peek, recognize, verify};
+use nom::sequence::{delimited, pair, preceded, terminated, tuple};
 use nom::IResult;
 
+pub use assertion::*;
 pub use binary_operators::*;
-pub use block::{Assignment, BlockExpr};
+pub use block::{Assignment, BlockExpr, Module};
 pub use comparison::*;
 pub use conditional::*;
 pub use expr::*;
@@ -13,11 +14,14 @@ pub use func::*;
 pub use logical::*;
 pub use spaces::*;
 pub use suffix_operators::*;
+pub use try_expr::*;
 pub use value::*;
 
-use crate::parser::block::block_body;
+use crate::parser::block::{block_body, module_body};
+use crate::resolver::ErrorWithLocation;
 use std::sync::Arc;
 
+mod assertion;
 mod binary_operators;
 mod block;
 mod comparison;
@@ -30,6 +34,7 @@ mod spaces;
 mod suffix_operators;
 #[cfg(test)]
 mod test_helpers;
+mod try_expr;
 mod value;
 
 pub type Span<'a> = nom_locate::LocatedSpan<&'a str, Arc<str>>;
@@ -37,10 +42,22 @@ pub type Span<'a> = nom_locate::LocatedSpan<&'a str, Arc<str>>;
 pub fn parse_unit(input: Span) -> IResult<Span, ExprWithLocation> {
     all_consuming(alt((
         preceded(
-            peek(pair(ml_space0, tag("let"))),
-            cut(map(block_body, |x| {
-                Expr::Block(x).with_location(input.clone())
-            })),
+            peek(pair(
+                ml_space0,
+                alt((
+                    tag("let"),
+                    terminated(tag("export"), ml_space1),
+                    terminated(tag("param"), ml_space1),
+                    recognize(tuple((tag("import"), ml_space0, tag("{")))),
+                )),
+            )),
+            cut(alt((
+                map(
+                    verify(all_consuming(module_body), Module::has_imports_or_exports),
+                    |x| Expr::Module(x).with_location(input.clone()),
+                ),
+                map(block_body, |x| Expr::Block(x).with_location(input.clone())),
+            ))),
         ),
         preceded(
             peek(tuple((ml_space0, identifier, ml_space0, tag(":")))),
@@ -51,3 +68,18 @@ pub fn parse_unit(input: Span) -> IResult<Span, ExprWithLocation> {
         delimited(ml_space0, expr, ml_space0),
     )))(input.clone())
 }
+
+/// Parses the whole source, unparsed trailing input is an error.
+pub fn parse_complete_unit(input: Span) -> Result<ExprWithLocation, ErrorWithLocation> {
+    let (rest, expr) = parse_unit(input)?;
+    if !rest.fragment().is_empty() {
+        return Err(anyhow!("Cannot parse (incomplete): '{}'", rest.fragment()).into());
+    }
+    Ok(expr)
+}
+
+/// Parses top-level statements that are not followed by an expression, as entered in the REPL.
+pub fn parse_statements(input: Span) -> Result<Module, ErrorWithLocation> {
+    let (_, module) = all_consuming(module_body)(input)?;
+    Ok(module)
+}
@@ -1,9 +1,11 @@
 use crate::parser::Span;
+use nom::branch::alt;
 use nom::bytes::complete::*;
 use nom::character::complete::{line_ending, not_line_ending};
 use nom::combinator::{map, opt, recognize, verify};
+use nom::error::ErrorKind;
 use nom::sequence::{pair, preceded, terminated};
-use nom::IResult;
+use nom::{IResult, InputTake};
 
 const SPACES: &str = " \t\r\n";
 
@@ -11,7 +13,7 @@ fn is_space_or_newline(c: char) -> bool {
     SPACES.contains(c)
 }
 
-pub fn ml_space1(input: Span) -> IResult<Span, &str> {
+pub fn ml_space1(input: Span<'_>) -> IResult<Span<'_>, &str> {
     verify(ml_space0, |x: &str| !x.is_empty())(input)
 }
 
@@ -30,11 +32,11 @@ fn test_ml_space1() {
     );
 }
 
-pub fn ml_space0(input: Span) -> IResult<Span, &str> {
+pub fn ml_space0(input: Span<'_>) -> IResult<Span<'_>, &str> {
     map(
         recognize(pair(
             take_while(is_space_or_newline),
-            opt(pair(line_comment, ml_space0)),
+            opt(pair(alt((line_comment, block_comment)), ml_space0)),
         )),
         |x| *x.fragment(),
     )(input)
@@ -45,11 +47,83 @@ fn test_ml_space0() {
     use crate::parser::test_helpers::span;
     assert_eq!(ml_space0(span("  // hello\n")).unwrap().1, "  // hello\n");
     assert_eq!(ml_space0(span("// hello\n//")).unwrap().1, "// hello\n//");
+    assert_eq!(
+        ml_space0(span(" /* a /* b */ c */ // d\n x")).unwrap().1,
+        " /* a /* b */ c */ // d\n "
+    );
 }
 
-fn line_comment(input: Span) -> IResult<Span, &str> {
+fn line_comment(input: Span<'_>) -> IResult<Span<'_>, &str> {
     map(
         preceded(tag("//"), opt(terminated(not_line_ending, line_ending))),
         |x| x.map(|a: Span| *a.fragment()).unwrap_or(""),
     )(input)
 }
+
+fn block_comment(input: Span<'_>) -> IResult<Span<'_>, &str> {
+    let (rest, _) = tag("/*")(input.clone())?;
+    let mut depth = 1;
+    let mut chars = rest.fragment().char_indices().peekable();
+    while let Some((ix, c)) = chars.next() {
+        let opens = c == '/' && chars.peek().map(|x| x.1) == Some('*');
+        let closes = c == '*' && chars.peek().map(|x| x.1) == Some('/');
+        if opens || closes {
+            chars.next();
+            depth = if opens { depth + 1 } else { depth - 1 };
+            if depth == 0 {
+                let (rest, comment) = input.take_split(ix + 4);
+                return Ok((rest, comment.fragment()));
+            }
+        }
+    }
+    Err(nom::Err::Failure(nom::error::Error {
+        input,
+        code: ErrorKind::TakeUntil,
+    }))
+}
+
+#[test]
+fn test_block_comment() {
+    use crate::parser::test_helpers::span;
+    assert_eq!(block_comment(span("/* a */b")).unwrap().1, "/* a */");
+    assert_eq!(
+        block_comment(span("/* a /* b */ */b")).unwrap().1,
+        "/* a /* b */ */"
+    );
+    assert!(matches!(
+        block_comment(span("/* a /* b */")),
+        Err(nom::Err::Failure(_))
+    ));
+}
+
+/// Extracts the `///` doc comment lines that immediately precede an item from
+/// the whitespace in front of it.
+pub fn doc_comment(space: &str) -> Option<String> {
+    let mut lines: Vec<&str> = space
+        .lines()
+        .rev()
+        .map(str::trim)
+        .skip_while(|x| x.is_empty())
+        .take_while(|x| x.starts_with("///") && !x.starts_with("////"))
+        .map(|x| {
+            let x = &x[3..];
+            x.strip_prefix(' ').unwrap_or(x)
+        })
+        .collect();
+    if lines.is_empty() {
+        None
+    } else {
+        lines.reverse();
+        Some(lines.join("\n"))
+    }
+}
+
+#[test]
+fn test_doc_comment() {
+    assert_eq!(
+        doc_comment("// comment\n  /// line 1\n  ///line 2\n  "),
+        Some("line 1\nline 2".to_string())
+    );
+    assert_eq!(doc_comment("/// doc\n// comment\n"), None);
+    assert_eq!(doc_comment("  \n  "), None);
+}
@@ -1,5 +1,5 @@
 use crate::parser::Span;
 
-pub fn span(content: &str) -> Span {
+pub fn span(content: &str) -> Span<'_> {
     Span::new_extra(content, "<test_string>".into())
 }
@@ -0,0 +1,28 @@
+use nom::bytes::complete::tag;
+use nom::combinator::{cut, map};
+use nom::sequence::{pair, tuple};
+use nom::IResult;
+
+use crate::parser::expr::expr;
+use crate::parser::{ml_space1, ExprWithLocation, Span};
+
+#[derive(Debug, PartialEq)]
+pub struct TryExpr<'a> {
+    pub expression: ExprWithLocation<'a>,
+    pub fallback: ExprWithLocation<'a>,
+}
+
+pub fn try_expr(input: Span) -> IResult<Span, TryExpr> {
+    map(
+        tuple((
+            pair(tag("try"), ml_space1),
+            cut(expr),
+            cut(tuple((ml_space1, tag("else"), ml_space1))),
+            cut(expr),
+        )),
+        |(_, expression, _, fallback)| TryExpr {
+            expression,
+            fallback,
+        },
+    )(input)
+}
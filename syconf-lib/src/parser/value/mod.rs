@@ -3,9 +3,10 @@ use nom::bytes::complete::*;
 
 use nom::combinator::map;
 
-use nom::multi::separated_list0;
+use nom::multi::{many0, separated_list0};
 
-use nom::sequence::{delimited, pair, separated_pair, tuple};
+use nom::combinator::opt;
+use nom::sequence::{delimited, pair, separated_pair, terminated, tuple};
 use nom::{IResult, InputLength, InputTake};
 use nom_locate::position;
 
@@ -30,6 +31,7 @@ pub enum ConfigValue<'a> {
 pub struct HashMapEntry<'a> {
     pub key: ExprWithLocation<'a>,
     pub value: ExprWithLocation<'a>,
+    pub doc: Option<String>,
 }
 
 pub fn config_value(input: Span) -> IResult<Span, ConfigValue> {
@@ -60,15 +62,19 @@ fn hashmap_entry(input: Span) -> IResult<Span, HashMapEntry> {
             tuple((ml_space0, tag(":"), ml_space0)),
             cut(expr),
         ),
-        |(key, value)| HashMapEntry { key, value },
+        |(key, value)| HashMapEntry {
+            key,
+            value,
+            doc: None,
+        },
     )(input)
 }
 
-fn raw_string(s: &str) -> Expr {
+fn raw_string(s: &str) -> Expr<'_> {
     Expr::Value(ConfigValue::String(vec![ConfigString::Raw(s)]))
 }
 
-fn sep(input: Span) -> IResult<Span, &str> {
+fn sep(input: Span<'_>) -> IResult<Span<'_>, &str> {
     let orig = input.clone();
     let (input, _) = ml_space0(input)?;
     let (input, _) = tag(",")(input)?;
@@ -89,16 +95,53 @@ fn list(input: Span) -> IResult<Span, Vec<ExprWithLocation>> {
     )(input)
 }
 
-fn hashmap(input: Span) -> IResult<Span, Vec<HashMapEntry>> {
+pub fn hashmap(input: Span) -> IResult<Span, Vec<HashMapEntry>> {
     delimited(tag("{"), hashmap_body, tag("}"))(input)
 }
 
 pub fn hashmap_body(input: Span) -> IResult<Span, Vec<HashMapEntry>> {
-    delimited(
-        ml_space0,
-        map(separated_list0(alt((sep, ml_space1)), hashmap_entry), |x| {
-            x.into_iter().collect()
-        }),
+    terminated(
+        map(
+            opt(pair(
+                documented_entry(ml_space0),
+                many0(documented_entry(alt((sep, ml_space1)))),
+            )),
+            |x| {
+                x.map(|(first, mut rest)| {
+                    rest.insert(0, first);
+                    rest
+                })
+                .unwrap_or_default()
+            },
+        ),
         alt((sep, ml_space0)),
     )(input)
 }
+
+fn documented_entry<'a>(
+    space: impl FnMut(Span<'a>) -> IResult<Span<'a>, &'a str>,
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, HashMapEntry<'a>> {
+    map(pair(space, hashmap_entry), |(space, mut entry)| {
+        entry.doc = doc_comment(space);
+        entry
+    })
+}
+
+#[test]
+fn hashmap_doc_comments() {
+    use crate::parser::test_helpers::span;
+    let entries = hashmap_body(span(
+        r#"
+    /// The port.
+    port: 80,
+    /// The host
+    /// name.
+    host: "localhost"
+    plain: 1
+    "#,
+    ))
+    .unwrap()
+    .1;
+    let docs: Vec<Option<&str>> = entries.iter().map(|x| x.doc.as_deref()).collect();
+    assert_eq!(docs, vec![Some("The port."), Some("The host\nname."), None]);
+}
@@ -1,9 +1,10 @@
 use nom::branch::alt;
 use nom::bytes::complete::{tag, take_while};
-use nom::combinator::{all_consuming, map};
+use nom::character::complete::line_ending;
+use nom::combinator::{all_consuming, map, opt};
 use nom::error::ErrorKind;
 use nom::multi::many0;
-use nom::sequence::{delimited, pair};
+use nom::sequence::{delimited, pair, tuple};
 use nom::{FindSubstring, IResult, InputLength, InputTake, Needed, Slice};
 
 use crate::parser::expr::expr;
@@ -13,9 +14,15 @@ use crate::parser::{ml_space0, ExprWithLocation, Span};
 pub enum ConfigString<'a> {
     Raw(&'a str),
     Interpolated(ExprWithLocation<'a>),
+    /// Interpolation inside a `"""` string, the lines of the value get indented to the column it starts at.
+    Indented(ExprWithLocation<'a>),
 }
 
 pub fn parse(input: Span) -> IResult<Span, Vec<ConfigString>> {
+    alt((heredoc, quoted))(input)
+}
+
+fn quoted(input: Span) -> IResult<Span, Vec<ConfigString>> {
     let (input, (hashes, quote)) =
         pair(take_while(|x| x == '#'), alt((tag("\""), tag("'"))))(input)?;
 
@@ -78,3 +85,146 @@ fn interpolated_string(input: Span) -> IResult<Span, ConfigString> {
         )),
     }
 }
+
+const HEREDOC_QUOTES: &str = "\"\"\"";
+
+/// Parses a `"""` string. The common indentation of its lines is stripped,
+/// and the trailing newline is kept only if the opening quotes are followed by `+`.
+fn heredoc(input: Span) -> IResult<Span, Vec<ConfigString>> {
+    let (body, (_, keep_newline, _, _)) = tuple((
+        tag(HEREDOC_QUOTES),
+        opt(tag("+")),
+        take_while(|x| x == ' ' || x == '\t'),
+        line_ending,
+    ))(input)?;
+
+    let end = match body.fragment().find_substring(HEREDOC_QUOTES) {
+        Some(x) => x,
+        None => return Err(nom::Err::Incomplete(Needed::Unknown)),
+    };
+    let rest = body.slice(end + HEREDOC_QUOTES.len()..);
+    let mut content = &body.fragment()[..end];
+    // The line with the closing quotes does not belong to the content if it is blank.
+    if let Some(ix) = content.rfind('\n') {
+        if content[ix + 1..].trim().is_empty() {
+            content = &content[..ix + 1];
+            if keep_newline.is_none() {
+                content = content.strip_suffix('\n').unwrap_or(content);
+                content = content.strip_suffix('\r').unwrap_or(content);
+            }
+        }
+    }
+
+    let pieces = all_consuming(many0(interpolated_string))(body.slice(..content.len()))?.1;
+    Ok((rest, unindent_pieces(pieces)))
+}
+
+fn is_indentation(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+fn common_indentation(pieces: &[ConfigString]) -> usize {
+    let mut min_indent: Option<usize> = None;
+    let mut indent = 0;
+    let mut at_line_start = true;
+    let mut add_content_line = |indent: usize| {
+        min_indent = Some(min_indent.map_or(indent, |x| x.min(indent)));
+    };
+    for piece in pieces {
+        match piece {
+            ConfigString::Raw(s) => {
+                for c in s.chars() {
+                    if c == '\n' {
+                        indent = 0;
+                        at_line_start = true;
+                    } else if at_line_start {
+                        if is_indentation(c) {
+                            indent += 1;
+                        } else if c != '\r' {
+                            add_content_line(indent);
+                            at_line_start = false;
+                        }
+                    }
+                }
+            }
+            _ => {
+                if at_line_start {
+                    add_content_line(indent);
+                    at_line_start = false;
+                }
+            }
+        }
+    }
+    min_indent.unwrap_or(0)
+}
+
+fn unindent_pieces(pieces: Vec<ConfigString>) -> Vec<ConfigString> {
+    let indent = common_indentation(&pieces);
+    let mut out = Vec::with_capacity(pieces.len());
+    // Number of indentation characters still to strip from the current line.
+    let mut to_strip = indent;
+    for piece in pieces {
+        match piece {
+            ConfigString::Raw(s) => {
+                let mut start = 0;
+                for (ix, c) in s.char_indices() {
+                    if to_strip > 0 && is_indentation(c) && start == ix {
+                        to_strip -= 1;
+                        start = ix + 1;
+                    } else if c == '\n' {
+                        out.push(ConfigString::Raw(&s[start..=ix]));
+                        start = ix + 1;
+                        to_strip = indent;
+                    } else {
+                        to_strip = 0;
+                    }
+                }
+                if start < s.len() {
+                    out.push(ConfigString::Raw(&s[start..]));
+                }
+            }
+            ConfigString::Interpolated(expr) | ConfigString::Indented(expr) => {
+                to_strip = 0;
+                out.push(ConfigString::Indented(expr));
+            }
+        }
+    }
+    out
+}
+
+#[test]
+fn heredoc_string() {
+    use crate::parser::test_helpers::span;
+
+    let raw = |x: &Vec<ConfigString>| -> Vec<String> {
+        x.iter()
+            .map(|x| match x {
+                ConfigString::Raw(s) => s.to_string(),
+                ConfigString::Interpolated(_) => "${}".to_string(),
+                ConfigString::Indented(_) => "${indented}".to_string(),
+            })
+            .collect()
+    };
+
+    assert_eq!(
+        raw(&parse(span("\"\"\"\n    a\n\n      b\n    \"\"\""))
+            .unwrap()
+            .1)
+        .concat(),
+        "a\n\n  b"
+    );
+    assert_eq!(
+        raw(&parse(span("\"\"\"+\n    a\n      b\n    \"\"\""))
+            .unwrap()
+            .1)
+        .concat(),
+        "a\n  b\n"
+    );
+    assert_eq!(
+        raw(&parse(span("\"\"\"\n    a: ${x}\n    ${y}\n  \"\"\""))
+            .unwrap()
+            .1)
+        .concat(),
+        "a: ${indented}\n${indented}"
+    );
+}
@@ -6,7 +6,7 @@ use crate::resolver::value::TypeMismatch;
 
 pub type Error = ErrorWithLocation;
 
-#[derive(thiserror::Error, Debug)]
+#[derive(thiserror::Error, Debug, Clone)]
 pub struct ErrorWithLocation {
     pub location: Option<Location>,
     pub message: String,
@@ -1,53 +1,119 @@
 use std::fs::File;
 use std::io::Read;
+use std::path::Path;
 use std::process::Command;
 
 use handlebars::Handlebars;
 
-use crate::resolver::value::FunctionSig;
+use std::sync::Arc;
+
+use crate::resolver::host::Host;
+use crate::resolver::value::{Func, FunctionSig};
 use crate::resolver::{Error, Value};
 
-pub fn lookup(function_name: &str) -> Option<&'static FunctionSig> {
-    Some(match function_name {
-        "read_file" => &read_file,
-        "getenv" => &getenv,
+pub fn lookup(function_name: &str, host: &Arc<Host>) -> Option<Func> {
+    let func: &'static FunctionSig = match function_name {
+        "read_file" => {
+            let host = host.clone();
+            return Some(Func::new_closure(move |args| read_file(&host, args)));
+        }
+        "getenv" => {
+            let host = host.clone();
+            return Some(Func::new_closure(move |args| getenv(&host, args)));
+        }
         "concat" => &concat,
-        "shell" => &shell,
+        "shell" => {
+            let host = host.clone();
+            return Some(Func::new_closure(move |args| shell(&host, args)));
+        }
         "handlebars" => &handlebars_template,
+        "error" => &error,
+        "ext" => {
+            let host = host.clone();
+            return Some(Func::new_closure(move |args| ext(&host, args)));
+        }
         _ => return None,
-    })
+    };
+    Some(Func::new_builtin(func))
 }
 
-fn read_file(args: &[Value]) -> Result<Value, Error> {
+fn read_file(host: &Host, args: &[Value]) -> Result<Value, Error> {
     check!(
         args.len() == 1,
         "'read_file' expects a single string argument"
     );
     let file_name = args[0].as_value_string()?;
+    let path = Path::new(file_name.as_ref());
+    let full_path = path
+        .canonicalize()
+        .or_else(|_| std::env::current_dir().map(|dir| dir.join(path)))
+        .unwrap_or_else(|_| path.to_path_buf());
+    host.dependencies.add_file(full_path.clone());
 
     let mut buf = String::new();
-    let mut f = File::open(file_name.as_ref())
-        .map_err(|e| anyhow!("Cannot open file '{}': {}", file_name, e))?;
-    f.read_to_string(&mut buf)
-        .map_err(|e| anyhow!("Cannot read file '{}': {}", file_name, e))?;
+    let content = File::open(path).and_then(|mut f| f.read_to_string(&mut buf));
+    host.inputs
+        .file(&full_path, content.as_ref().ok().map(|_| buf.as_str()))?;
+    content.map_err(|e| anyhow!("Cannot read file '{}': {}", file_name, e))?;
     Ok(Value::String(buf.into()))
 }
 
-fn getenv(args: &[Value]) -> Result<Value, Error> {
+fn getenv(host: &Host, args: &[Value]) -> Result<Value, Error> {
     check!(
         !args.is_empty() && args.len() <= 2,
         "'getenv' expects a string argument with an optional default value"
     );
     let envname = args[0].as_value_string()?;
-    std::env::var(envname.as_ref())
-        .map(|x| Value::String(x.into()))
-        .or_else(|_| {
-            if args.len() == 2 {
-                Ok(args[1].clone())
-            } else {
-                Err(format!("Environment variable '{}' is not set", envname).into())
-            }
-        })
+    let value = std::env::var(envname.as_ref());
+    host.inputs.env(envname, value.as_deref().ok())?;
+    value.map(|x| Value::String(x.into())).or_else(|_| {
+        if args.len() == 2 {
+            Ok(args[1].clone())
+        } else {
+            Err(format!("Environment variable '{}' is not set", envname).into())
+        }
+    })
+}
+
+fn ext(host: &Host, args: &[Value]) -> Result<Value, Error> {
+    check!(
+        !args.is_empty() && args.len() <= 2,
+        "'ext' expects a string argument with an optional default value"
+    );
+    let name = args[0].as_value_string()?;
+    match (host.ext_vars.get(name.as_ref()), args.get(1)) {
+        (Some(value), _) => Ok(value.clone()),
+        (None, Some(default)) => Ok(default.clone()),
+        (None, None) => Err(format!("External variable '{}' is not set", name).into()),
+    }
+}
+
+#[test]
+fn test_ext() {
+    let options = crate::Options {
+        ext_vars: vec![("cluster".to_string(), Value::String("eu1".into()))]
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+    assert_eq!(
+        crate::parse_string_with_options(
+            r#"[ext("cluster"), ext("region", "eu-central-1")]"#,
+            &options
+        )
+        .unwrap(),
+        Value::List(
+            vec![
+                Value::String("eu1".into()),
+                Value::String("eu-central-1".into())
+            ]
+            .into()
+        )
+    );
+    let err = crate::parse_string_with_options(r#"ext("region")"#, &options)
+        .err()
+        .unwrap();
+    assert_eq!(err.message, "External variable 'region' is not set");
 }
 
 pub fn concat_strings(args: &[Value]) -> Result<Value, Error> {
@@ -63,6 +129,32 @@ pub fn concat_strings(args: &[Value]) -> Result<Value, Error> {
     Ok(Value::String(out.into()))
 }
 
+/// Concatenates the arguments as strings. All lines of the last argument but the first one
+/// are indented to the column the last argument starts at.
+pub fn indent_lines(args: &[Value]) -> Result<Value, Error> {
+    check!(!args.is_empty(), "expects at least 1 argument");
+    let (value, prefix) = args.split_last().unwrap();
+    let prefix = concat_strings(prefix)?;
+    let prefix = prefix.as_value_string()?;
+    let value = concat_strings(std::slice::from_ref(value))?;
+    let column = prefix.rsplit('\n').next().unwrap_or("").chars().count();
+    let indentation = " ".repeat(column);
+    let out = value
+        .as_value_string()?
+        .split('\n')
+        .enumerate()
+        .map(|(ix, line)| {
+            if ix == 0 || line.is_empty() {
+                line.to_string()
+            } else {
+                format!("{}{}", indentation, line)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    Ok(Value::String(format!("{}{}", prefix, out).into()))
+}
+
 #[test]
 fn func_concat_strings() {
     assert_eq!(
@@ -99,11 +191,12 @@ fn func_concat() {
     );
 }
 
-fn shell(args: &[Value]) -> Result<Value, Error> {
+fn shell(host: &Host, args: &[Value]) -> Result<Value, Error> {
     check!(args.len() == 1, "SHELL requires a single string argument");
 
+    let script = args[0].as_value_string()?;
     let mut cmd = Command::new("sh");
-    cmd.arg("-c").arg(args[0].as_value_string()?.as_ref());
+    cmd.arg("-c").arg(script.as_ref());
     let out = cmd
         .output()
         .map_err(|e| format!("cannot execute shell script: {}", e))?;
@@ -115,6 +208,7 @@ fn shell(args: &[Value]) -> Result<Value, Error> {
 
     let s =
         String::from_utf8(out.stdout).map_err(|_| "shell script did not return UTF-8 string")?;
+    host.inputs.command(script, &s)?;
     Ok(Value::String(s.into()))
 }
 
@@ -152,3 +246,23 @@ fn test_template() {
         Value::Bool(true)
     );
 }
+
+fn error(args: &[Value]) -> Result<Value, Error> {
+    check!(args.len() == 1, "'error' expects a single string argument");
+    Err(args[0].as_value_string()?.to_string().into())
+}
+
+#[test]
+fn test_error() {
+    let err = crate::parse_string(
+        r#"
+    let check = (x) => if x > 0 then x else error("x must be positive")
+    in
+    check(-1)
+    "#,
+    )
+    .err()
+    .unwrap();
+    assert_eq!(err.message, "x must be positive");
+    assert_eq!(err.location.unwrap().line, 2);
+}
@@ -0,0 +1,74 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::resolver::inputs::Inputs;
+use crate::resolver::node::CodeNode;
+use crate::resolver::Value;
+
+/// Values supplied by the application that runs the evaluation.
+#[derive(Debug, Default)]
+pub struct Host {
+    pub ext_vars: HashMap<String, Value>,
+    pub import_cache: ImportCache,
+    pub dependencies: Dependencies,
+    pub inputs: Inputs,
+}
+
+/// Imported files that are already parsed.
+#[derive(Debug, Default)]
+pub struct ImportCache(Arc<Mutex<HashMap<String, CodeNode>>>);
+
+impl ImportCache {
+    pub(crate) fn get(&self, key: &str) -> Option<CodeNode> {
+        self.0.lock().expect("cannot lock").get(key).cloned()
+    }
+
+    pub(crate) fn insert(&self, key: String, node: CodeNode) {
+        self.0.lock().expect("cannot lock").insert(key, node);
+    }
+}
+
+/// Files read during the evaluation, i.e. the evaluated file, its imports and the files read by `read_file`,
+/// and the import edges between them. All clones share the same data.
+#[derive(Clone, Debug, Default)]
+pub struct Dependencies(Arc<Mutex<DependenciesRef>>);
+
+#[derive(Debug, Default)]
+struct DependenciesRef {
+    files: BTreeSet<PathBuf>,
+    imports: BTreeSet<(PathBuf, PathBuf)>,
+}
+
+impl Dependencies {
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.0
+            .lock()
+            .expect("cannot lock")
+            .files
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Pairs of the importing and the imported file.
+    pub fn imports(&self) -> Vec<(PathBuf, PathBuf)> {
+        self.0
+            .lock()
+            .expect("cannot lock")
+            .imports
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn add_file(&self, path: PathBuf) {
+        self.0.lock().expect("cannot lock").files.insert(path);
+    }
+
+    pub(crate) fn add_import(&self, from: PathBuf, to: PathBuf) {
+        let mut deps = self.0.lock().expect("cannot lock");
+        deps.files.insert(to.clone());
+        deps.imports.insert((from, to));
+    }
+}
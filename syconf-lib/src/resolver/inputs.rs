@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use sha2::{Digest, Sha256};
+
+/// Everything an evaluation consumed from the outside.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct InputsReport {
+    /// Environment variables read by `getenv`, `None` if a variable is not set.
+    pub env: BTreeMap<String, Option<String>>,
+    /// SHA-256 hashes of the evaluated file, its imports and the files read by `read_file`,
+    /// `None` if a file cannot be read.
+    pub files: BTreeMap<PathBuf, Option<String>>,
+    /// SHA-256 hashes of the output of the commands run by `shell`.
+    pub commands: BTreeMap<String, String>,
+}
+
+/// Records the inputs of the evaluation. All clones share the same report.
+/// If the recorder is frozen, the evaluation fails when an input is not the same as in the frozen report.
+#[derive(Clone, Debug, Default)]
+pub struct Inputs(Arc<Mutex<InputsRef>>);
+
+#[derive(Debug, Default)]
+struct InputsRef {
+    report: InputsReport,
+    frozen: Option<InputsReport>,
+    // The first input that is not the same as in the frozen report.
+    violation: Option<String>,
+}
+
+impl Inputs {
+    pub fn frozen(report: InputsReport) -> Self {
+        Self(Arc::new(Mutex::new(InputsRef {
+            report: InputsReport::default(),
+            frozen: Some(report),
+            violation: None,
+        })))
+    }
+
+    /// Returns a new recorder with the same frozen report.
+    pub fn cleared(&self) -> Self {
+        Self(Arc::new(Mutex::new(InputsRef {
+            report: InputsReport::default(),
+            frozen: self.0.lock().expect("cannot lock").frozen.clone(),
+            violation: None,
+        })))
+    }
+
+    pub fn report(&self) -> InputsReport {
+        self.0.lock().expect("cannot lock").report.clone()
+    }
+
+    /// Returns and clears the error of the first input that was not the same as in the frozen report.
+    /// The evaluation must fail with it even if it recovered from the error with `try`.
+    pub(crate) fn take_violation(&self) -> Option<String> {
+        self.0.lock().expect("cannot lock").violation.take()
+    }
+
+    pub(crate) fn env(&self, name: &str, value: Option<&str>) -> Result<(), String> {
+        let value = value.map(String::from);
+        let mut inputs = self.0.lock().expect("cannot lock");
+        let checked = match &inputs.frozen {
+            Some(frozen) => check_frozen(
+                frozen.env.get(name),
+                &value,
+                format!("Environment variable '{}'", name),
+            ),
+            None => Ok(()),
+        };
+        inputs.remember_violation(checked)?;
+        inputs.report.env.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    pub(crate) fn file(&self, path: &Path, content: Option<&str>) -> Result<(), String> {
+        let hash = content.map(sha256);
+        let mut inputs = self.0.lock().expect("cannot lock");
+        let checked = match &inputs.frozen {
+            Some(frozen) => check_frozen(
+                frozen.files.get(path),
+                &hash,
+                format!("File '{}'", path.display()),
+            ),
+            None => Ok(()),
+        };
+        inputs.remember_violation(checked)?;
+        inputs.report.files.insert(path.to_path_buf(), hash);
+        Ok(())
+    }
+
+    pub(crate) fn command(&self, command: &str, output: &str) -> Result<(), String> {
+        let hash = sha256(output);
+        let mut inputs = self.0.lock().expect("cannot lock");
+        let checked = match &inputs.frozen {
+            Some(frozen) => check_frozen(
+                frozen.commands.get(command),
+                &hash,
+                format!("Output of command '{}'", command),
+            ),
+            None => Ok(()),
+        };
+        inputs.remember_violation(checked)?;
+        inputs.report.commands.insert(command.to_string(), hash);
+        Ok(())
+    }
+}
+
+impl InputsRef {
+    fn remember_violation(&mut self, checked: Result<(), String>) -> Result<(), String> {
+        if let Err(e) = &checked {
+            self.violation.get_or_insert_with(|| e.clone());
+        }
+        checked
+    }
+}
+
+fn check_frozen<T: PartialEq>(frozen: Option<&T>, actual: &T, input: String) -> Result<(), String> {
+    match frozen {
+        None => Err(format!("{} is not in the frozen inputs", input)),
+        Some(frozen) if frozen != actual => {
+            Err(format!("{} differs from the frozen inputs", input))
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+fn sha256(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
@@ -8,6 +8,9 @@ use crate::resolver::{Error, Value};
 pub type HashmapMethod =
     dyn Fn(&HashMap<ValueString, Value>, &[Value]) -> Result<Value, Error> + Send + Sync;
 
+/// Names of the hashmap methods.
+pub const METHODS: &[&str] = &["map", "filter", "len", "insert", "merge", "drop", "to_list"];
+
 pub fn method(name: &str) -> Option<&'static HashmapMethod> {
     Some(match name {
         "map" => &map,
@@ -141,7 +144,7 @@ fn merge(hm: &HashMap<ValueString, Value>, args: &[Value]) -> Result<Value, Erro
     Ok(Value::HashMap(Arc::new(out)))
 }
 
-fn merge_raw(
+pub fn merge_raw(
     mut dest: HashMap<ValueString, Value>,
     src: &HashMap<ValueString, Value>,
 ) -> HashMap<ValueString, Value> {
@@ -6,6 +6,17 @@ use crate::resolver::{Error, Value};
 
 pub type ListMethod = dyn Fn(&[Value], &[Value]) -> Result<Value, Error> + Send + Sync;
 
+/// Names of the list methods.
+pub const METHODS: &[&str] = &[
+    "map",
+    "filter",
+    "len",
+    "append",
+    "join",
+    "fold",
+    "to_hashmap",
+];
+
 pub fn method(name: &str) -> Option<&'static ListMethod> {
     Some(match name {
         "map" => &map,
@@ -23,7 +34,7 @@ fn map(list: &[Value], args: &[Value]) -> Result<Value, Error> {
     let func = ValueExtractor::new(args, 1)?.extract_func(0)?;
     let mapped = list
         .iter()
-        .map(|x| func.call(&[x.clone()]))
+        .map(|x| func.call(std::slice::from_ref(x)))
         .collect::<Result<Vec<Value>, Error>>()?;
     Ok(Value::List(mapped.into()))
 }
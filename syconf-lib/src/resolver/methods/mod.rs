@@ -5,6 +5,16 @@ pub mod hashmap;
 pub mod list;
 pub mod string;
 
+/// Names of the methods that can be called on the value.
+pub fn names(value: &Value) -> &'static [&'static str] {
+    match value {
+        Value::HashMap(_) => hashmap::METHODS,
+        Value::List(_) => list::METHODS,
+        Value::String(_) => string::METHODS,
+        _ => &[],
+    }
+}
+
 pub fn index(args: &[Value]) -> Result<Value, Error> {
     debug!(?args, "index");
     match &args[0] {
@@ -68,3 +78,12 @@ fn method_index() {
         Value::Bool(true)
     );
 }
+
+#[test]
+fn method_names() {
+    assert!(hashmap::METHODS
+        .iter()
+        .all(|x| hashmap::method(x).is_some()));
+    assert!(list::METHODS.iter().all(|x| list::method(x).is_some()));
+    assert!(string::METHODS.iter().all(|x| string::method(x).is_some()));
+}
@@ -5,6 +5,18 @@ use crate::resolver::{Error, Value};
 
 pub type StringMethod = dyn Fn(&str, &[Value]) -> Result<Value, Error> + Send + Sync;
 
+/// Names of the string methods.
+pub const METHODS: &[&str] = &[
+    "parse_json",
+    "parse_yaml",
+    "parse_toml",
+    "trim",
+    "split",
+    "lines",
+    "script",
+    "oneline",
+];
+
 pub fn method(method_name: &str) -> Option<&'static StringMethod> {
     Some(match method_name {
         "parse_json" => &parse_json,
@@ -1,24 +1,51 @@
 use std::collections::HashMap;
 
 use context::Context;
+pub use host::Dependencies;
+pub use inputs::{Inputs, InputsReport};
+pub use session::{Evaluated, Session};
 pub use value::{Func, SerializableValue, TypeMismatch, Value, ValueString};
+pub use value_diff::{diff, Change};
 
 use crate::parser::ExprWithLocation;
+use crate::Options;
 pub use error::{Error, ErrorWithLocation};
 
 #[macro_use]
 mod error;
 mod context;
 mod functions;
+mod host;
+mod inputs;
 mod methods;
 mod node;
 mod operators;
+mod session;
 mod tree_builder;
 mod value;
+mod value_diff;
 mod value_extraction;
+mod value_format;
+mod value_path;
 
-pub fn resolve(expr: &ExprWithLocation) -> Result<Value, Error> {
-    let node = tree_builder::NodeTreeBuilder.build_tree(&Context::empty(), expr)?;
-    debug!(?node, "compiled node");
-    node.resolve(&Context::empty())
+/// Evaluates top-level expressions with the same options. The imports are parsed once per evaluator.
+pub struct Evaluator(tree_builder::NodeTreeBuilder);
+
+impl Evaluator {
+    pub fn new(options: &Options) -> Self {
+        Self(tree_builder::NodeTreeBuilder::new(options))
+    }
+
+    pub fn resolve(&self, expr: &ExprWithLocation) -> Result<Value, Error> {
+        let node = self
+            .0
+            .build_file(&Context::empty(), expr, &Default::default())?;
+        debug!(?node, "compiled node");
+        node.resolve(&Context::empty())
+    }
+
+    /// Fails if an input differed from the frozen inputs during the evaluation.
+    pub fn check_inputs(&self) -> Result<(), Error> {
+        self.0.check_inputs()
+    }
 }
@@ -16,6 +16,8 @@ pub struct FunctionDefinition {
 #[derive(Debug)]
 pub enum NodeContent {
     Resolved(Value),
+    // An error that is raised only if the node gets resolved.
+    Failed(ErrorWithLocation),
 
     List(Vec<CodeNode>),
     HashMap(Vec<HmEntry>),
@@ -26,6 +28,17 @@ pub enum NodeContent {
         then_branch: CodeNode,
         else_branch: CodeNode,
     },
+    Assertion {
+        condition: CodeNode,
+        message: Option<CodeNode>,
+        expression: CodeNode,
+    },
+    Try {
+        expression: CodeNode,
+        fallback: CodeNode,
+        // The fallback is a function literal that is called with the error message.
+        handler: bool,
+    },
 
     FunctionInputArgument(String),
     FunctionCall {
@@ -65,6 +78,7 @@ impl CodeNode {
     pub fn resolve(&self, ctx: &Context) -> Result<Value, ErrorWithLocation> {
         match &self.0.content {
             NodeContent::Resolved(v) => Ok(v.clone()),
+            NodeContent::Failed(e) => Err(e.clone()),
             NodeContent::FunctionInputArgument(name) => ctx
                 .get_value(name)
                 .ok_or_else(|| self.err(format!("Function argument '{}' is not bound", name)))
@@ -83,26 +97,74 @@ impl CodeNode {
                     false_branch.resolve(ctx)
                 }
             }
+            NodeContent::Assertion {
+                condition,
+                message,
+                expression,
+            } => {
+                if condition.resolve(ctx)?.as_bool()? {
+                    expression.resolve(ctx)
+                } else {
+                    let message = match message {
+                        Some(msg) => format!(
+                            "Assertion failed: {}",
+                            msg.resolve(ctx)?
+                                .as_value_string()
+                                .map_err(|e| msg.err(e.to_string()))?
+                        ),
+                        None => "Assertion failed".to_string(),
+                    };
+                    Err(self.err(message))
+                }
+            }
+            NodeContent::Try {
+                expression,
+                fallback,
+                handler,
+            } => match expression.resolve(ctx) {
+                Ok(v) => Ok(v),
+                Err(e) => {
+                    debug!(%e, "recovering from error");
+                    match fallback.resolve(ctx)? {
+                        Value::Func(func) if *handler => func
+                            .call(&[Value::String(e.message.into())])
+                            .map_err(|e| fallback.add_location(e)),
+                        v => Ok(v),
+                    }
+                }
+            },
             NodeContent::List(list) => list
                 .iter()
                 .map(|x| x.resolve(ctx))
                 .collect::<Result<Vec<Value>, ErrorWithLocation>>()
                 .map(Into::into)
                 .map(Value::List),
-            NodeContent::HashMap(hm) => hm
-                .iter()
-                .map(|HmEntry { key, value }| {
-                    Ok((
-                        key.resolve(ctx)?
-                            .as_value_string()
-                            .map_err(|e| self.err(e.to_string()))?
-                            .clone(),
-                        value.resolve(ctx)?,
-                    ))
-                })
-                .collect::<Result<HashMap<ValueString, Value>, ErrorWithLocation>>()
-                .map(Arc::new)
-                .map(Value::HashMap),
+            NodeContent::HashMap(hm) => {
+                let mut out = HashMap::with_capacity(hm.len());
+                let mut key_nodes: HashMap<ValueString, &CodeNode> =
+                    HashMap::with_capacity(hm.len());
+                for HmEntry { key, value } in hm {
+                    let key_string = key
+                        .resolve(ctx)?
+                        .as_value_string()
+                        .map_err(|e| self.err(e.to_string()))?
+                        .clone();
+                    if let Some(first) = key_nodes.insert(key_string.clone(), key) {
+                        return Err(key.err(format!(
+                            "Duplicate key '{}'{}",
+                            key_string,
+                            first
+                                .0
+                                .location
+                                .as_ref()
+                                .map(|loc| format!(", first defined at {}", loc))
+                                .unwrap_or_default()
+                        )));
+                    }
+                    out.insert(key_string, value.resolve(ctx)?);
+                }
+                Ok(Value::HashMap(Arc::new(out)))
+            }
             NodeContent::FunctionCall {
                 name: _,
                 function,
@@ -127,6 +189,32 @@ impl CodeNode {
         }
     }
 
+    /// Returns the value of an already resolved node, e.g. a constant.
+    pub fn resolved_value(&self) -> Option<&Value> {
+        match &self.0.content {
+            NodeContent::Resolved(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the node of a hashmap field without resolving the other fields.
+    /// Only fields with constant keys can be found.
+    pub fn field(&self, name: &str) -> Option<CodeNode> {
+        match &self.0.content {
+            NodeContent::Resolved(Value::HashMap(hm)) => hm
+                .get(name)
+                .map(|x| CodeNode::new(NodeContent::Resolved(x.clone()), None)),
+            NodeContent::HashMap(entries) => entries
+                .iter()
+                .find(|x| match &x.key.0.content {
+                    NodeContent::Resolved(Value::String(key)) => key.as_ref() == name,
+                    _ => false,
+                })
+                .map(|x| x.value.clone()),
+            _ => None,
+        }
+    }
+
     fn err(&self, message: String) -> ErrorWithLocation {
         ErrorWithLocation {
             message,
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use crate::parser::{parse_complete_unit, parse_statements, Assignment, Expr, Span};
+use crate::resolver::context::Context;
+use crate::resolver::node::CodeNode;
+use crate::resolver::tree_builder::NodeTreeBuilder;
+use crate::resolver::{Error, ErrorWithLocation, Value};
+use crate::Options;
+
+/// Evaluates expressions one after another, `let` bindings are kept between the evaluations.
+pub struct Session {
+    builder: NodeTreeBuilder,
+    ctx: Context,
+}
+
+impl Session {
+    pub fn new(options: &Options) -> Self {
+        Self {
+            builder: NodeTreeBuilder::new(options),
+            ctx: Context::empty(),
+        }
+    }
+
+    /// Evaluates an expression, or adds the bindings of `let` statements and returns their names.
+    pub fn eval(&mut self, source: &str) -> Result<Evaluated, Error> {
+        let input = Span::new_extra(source, "<repl>".into());
+        if let Ok(module) = parse_statements(input.clone()) {
+            return self
+                .bind(&module.assignments, None)
+                .map(Evaluated::Bindings);
+        }
+        let expr = parse_complete_unit(input)?;
+        let value = self
+            .builder
+            .build_tree(&self.ctx, &expr)?
+            .resolve(&Context::empty());
+        let checked = self.builder.check_inputs();
+        value.and_then(|value| checked.map(|_| Evaluated::Value(value)))
+    }
+
+    /// Adds the top-level bindings of the file and returns their names.
+    pub fn load_file(&mut self, file_name: &str) -> Result<Vec<String>, Error> {
+        let read_error = |e: std::io::Error| ErrorWithLocation {
+            location: None,
+            message: format!("Cannot read file '{}': {}", file_name, e),
+        };
+        let content = read_to_string(file_name).map_err(read_error)?;
+        let normalized_fn = Path::new(file_name).canonicalize().map_err(read_error)?;
+        let expr = parse_complete_unit(Span::new_extra(
+            &content,
+            normalized_fn.to_string_lossy().into(),
+        ))?;
+        match &expr.inner {
+            // Parameters of the file take their default values.
+            Expr::Module(module) => self.bind(&module.assignments, Some(&HashMap::new())),
+            Expr::Block(block) => self.bind(&block.local_assignments, Some(&HashMap::new())),
+            _ => Err(format!("'{}' has no top-level bindings", file_name).into()),
+        }
+    }
+
+    fn bind(
+        &mut self,
+        assignments: &[Assignment],
+        params: Option<&HashMap<String, CodeNode>>,
+    ) -> Result<Vec<String>, Error> {
+        // Bindings are added to a new scope, so that a failing statement leaves the session unchanged.
+        let ctx = self.ctx.new_child();
+        let bound = self.builder.bind_statements(&ctx, assignments, params);
+        let checked = self.builder.check_inputs();
+        bound.and(checked)?;
+        self.ctx = ctx;
+        Ok(assignments.iter().map(|x| x.0.to_string()).collect())
+    }
+}
+
+/// Result of evaluating a source in a [`Session`].
+#[derive(Debug, PartialEq)]
+pub enum Evaluated {
+    Value(Value),
+    Bindings(Vec<String>),
+}
+
+#[test]
+fn session() {
+    use crate::parser::Number;
+    let mut session = Session::new(&Options::default());
+    assert_eq!(
+        session.eval("let a = 2\nlet f = (x) => x * a").unwrap(),
+        Evaluated::Bindings(vec!["a".to_string(), "f".to_string()])
+    );
+    assert_eq!(
+        session.eval("f(3)").unwrap(),
+        Evaluated::Value(Value::Number(Number::Int(6)))
+    );
+    assert!(session.eval("let b = nope").is_err());
+    assert_eq!(
+        session.eval("let a = 5").unwrap(),
+        Evaluated::Bindings(vec!["a".to_string()])
+    );
+    // `f` keeps the binding of `a` it was defined with.
+    assert_eq!(
+        session.eval("[a, f(1)]").unwrap(),
+        Evaluated::Value(crate::parse_string("[5, 2]").unwrap())
+    );
+}
@@ -1,30 +1,66 @@
+use std::collections::HashMap;
 use std::fs::read_to_string;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 
 use crate::parser::string::ConfigString;
 use crate::parser::*;
 use crate::parser::{Expr, ExprWithLocation};
 use crate::resolver::context::Context;
+use crate::resolver::error::Location;
+use crate::resolver::host::Host;
 use crate::resolver::node::{CodeNode, FunctionDefinition, HmEntry, NodeContent};
 use crate::resolver::value::{Func, FunctionSig, Value};
 use crate::resolver::{methods, operators, Error, ErrorWithLocation};
+use crate::Options;
 
-pub struct NodeTreeBuilder;
+pub struct NodeTreeBuilder {
+    // Locations of the imports that lead to the source being built.
+    import_chain: Vec<Location>,
+    lib_dirs: Arc<[PathBuf]>,
+    host: Arc<Host>,
+}
 
 impl NodeTreeBuilder {
+    pub fn new(options: &Options) -> Self {
+        Self {
+            import_chain: vec![],
+            lib_dirs: options.lib_dirs.clone().into(),
+            host: Arc::new(Host {
+                ext_vars: options.ext_vars.clone(),
+                import_cache: Default::default(),
+                dependencies: options.dependencies.clone(),
+                inputs: options.inputs.clone(),
+            }),
+        }
+    }
+
     pub fn build_tree(&self, ctx: &Context, expr: &ExprWithLocation) -> Result<CodeNode, Error> {
         let cell = match &expr.inner {
             Expr::Value(val) => self.config_value(ctx, val)?,
-            Expr::Block(block) => return self.block(ctx, block),
+            Expr::Block(block) => return self.block(ctx, block, None),
             Expr::Identifier(id) => self.identifier(ctx, id, &expr.location)?,
             Expr::FuncDefinition(fd) => self.func_definition(ctx, fd)?,
             Expr::BinaryOperator(op) => self.math_op(ctx, op)?,
             Expr::Comparison(cmp) => self.comparison(ctx, cmp)?,
             Expr::Conditional(cond) => self.conditional(ctx, cond)?,
+            Expr::Assertion(assertion) => self.assertion(ctx, assertion)?,
+            Expr::Try(try_expr) => self.try_expr(ctx, try_expr)?,
             Expr::Logical(logical) => self.logical(ctx, logical)?,
             Expr::Suffix(suffix) => self.suffix_operator(ctx, suffix)?,
-            Expr::Import(path) => return self.import(path, ctx, &expr.location),
+            Expr::Import(path, params) => return self.import(path, params, ctx, &expr.location),
+            Expr::SelectiveImport(path, name) => {
+                return self.selective_import(path, name, ctx, &expr.location)
+            }
+            Expr::ImportGlob(pattern) => self.import_glob(pattern, ctx, &expr.location)?,
+            Expr::Module(module) => self.module(ctx, module, None)?,
+            Expr::Param(..) => {
+                return Err(ErrorWithLocation {
+                    location: Some((&expr.location).into()),
+                    message: "Parameters can only be declared at the top level of a file"
+                        .to_string(),
+                })
+            }
         };
         Ok(CodeNode::new(cell, Some((&expr.location).into())))
     }
@@ -86,6 +122,30 @@ impl NodeTreeBuilder {
         })
     }
 
+    fn assertion(&self, ctx: &Context, assertion: &Assertion) -> Result<NodeContent, Error> {
+        Ok(NodeContent::Assertion {
+            condition: self.build_tree(ctx, &assertion.condition)?,
+            message: assertion
+                .message
+                .as_ref()
+                .map(|x| self.build_tree(ctx, x))
+                .transpose()?,
+            expression: self.build_tree(ctx, &assertion.expression)?,
+        })
+    }
+
+    fn try_expr(&self, ctx: &Context, try_expr: &TryExpr) -> Result<NodeContent, Error> {
+        // Errors of the guarded expression, e.g. a failed import, must be recoverable as well.
+        let expression = self
+            .build_tree(ctx, &try_expr.expression)
+            .unwrap_or_else(|e| CodeNode::new(NodeContent::Failed(e), None));
+        Ok(NodeContent::Try {
+            expression,
+            fallback: self.build_tree(ctx, &try_expr.fallback)?,
+            handler: matches!(try_expr.fallback.inner, Expr::FuncDefinition(_)),
+        })
+    }
+
     fn comparison(&self, ctx: &Context, cmp: &Comparison) -> Result<NodeContent, Error> {
         let args = vec![
             self.build_tree(ctx, &cmp.expr1)?,
@@ -125,7 +185,7 @@ impl NodeTreeBuilder {
             ConfigValue::String(s) => self.string(ctx, s),
             ConfigValue::HashMap(hm) => hm
                 .iter()
-                .map(|HashMapEntry { key, value }| {
+                .map(|HashMapEntry { key, value, .. }| {
                     Ok(HmEntry {
                         key: self.build_tree(ctx, key)?,
                         value: self.build_tree(ctx, value)?,
@@ -142,16 +202,39 @@ impl NodeTreeBuilder {
     }
 
     fn string(&self, ctx: &Context, cs: &[ConfigString]) -> Result<NodeContent, Error> {
-        let kids = cs
-            .iter()
-            .map(|x| match x {
-                ConfigString::Raw(s) => Ok(CodeNode::new(
-                    NodeContent::Resolved(Value::String((*s).into())),
-                    None,
-                )),
-                ConfigString::Interpolated(a) => self.build_tree(ctx, a),
-            })
-            .collect::<Result<Vec<CodeNode>, Error>>()?;
+        if let [ConfigString::Raw(s)] = cs {
+            return Ok(NodeContent::Resolved(Value::String((*s).into())));
+        }
+        let mut kids = Vec::with_capacity(cs.len());
+        // Index of the first piece of the current line.
+        let mut line_start = 0;
+        for x in cs {
+            match x {
+                ConfigString::Raw(s) => {
+                    kids.push(CodeNode::new(
+                        NodeContent::Resolved(Value::String((*s).into())),
+                        None,
+                    ));
+                    if s.ends_with('\n') {
+                        line_start = kids.len();
+                    }
+                }
+                ConfigString::Interpolated(a) => kids.push(self.build_tree(ctx, a)?),
+                ConfigString::Indented(a) => {
+                    // The column of the value is known only once the line before it is evaluated.
+                    let mut arguments = kids.split_off(line_start);
+                    arguments.push(self.build_tree(ctx, a)?);
+                    kids.push(CodeNode::new(
+                        NodeContent::FunctionCall {
+                            function: builtin_func_node(&super::functions::indent_lines),
+                            arguments: Some(arguments),
+                            name: "indent_lines".to_string(),
+                        },
+                        Some((&a.location).into()),
+                    ));
+                }
+            }
+        }
         Ok(NodeContent::FunctionCall {
             function: builtin_func_node(&super::functions::concat_strings),
             arguments: Some(kids),
@@ -159,22 +242,92 @@ impl NodeTreeBuilder {
         })
     }
 
-    fn block(&self, ctx: &Context, block: &BlockExpr) -> Result<CodeNode, Error> {
-        let ns = ctx.new_child();
-        debug!(?block.local_assignments, "block");
-        for Assignment(id, ex) in &block.local_assignments {
+    /// Fails if an input differed from the frozen inputs, also if the evaluation recovered from the error.
+    pub fn check_inputs(&self) -> Result<(), Error> {
+        match self.host.inputs.take_violation() {
+            Some(message) => Err(message.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Builds the top-level expression of a file. Its `param` statements take the values
+    /// given by the importer, or their defaults.
+    pub fn build_file(
+        &self,
+        ctx: &Context,
+        expr: &ExprWithLocation,
+        params: &HashMap<String, CodeNode>,
+    ) -> Result<CodeNode, Error> {
+        match &expr.inner {
+            Expr::Block(block) => self.block(ctx, block, Some(params)),
+            Expr::Module(module) => Ok(CodeNode::new(
+                self.module(ctx, module, Some(params))?,
+                Some((&expr.location).into()),
+            )),
+            _ => self.build_tree(ctx, expr),
+        }
+    }
+
+    /// Binds the statements in the context. Parameters are allowed only if `params` is given.
+    pub fn bind_statements(
+        &self,
+        ns: &Context,
+        assignments: &[Assignment],
+        params: Option<&HashMap<String, CodeNode>>,
+    ) -> Result<(), Error> {
+        for Assignment(id, ex, _) in assignments {
             debug!(?id, ?ex, "assignment1");
-            let node = self.build_tree(&ns, ex)?;
+            let node = match (&ex.inner, params) {
+                (Expr::Param(name, default), Some(params)) => {
+                    self.param(ns, name, default, params, &ex.location)?
+                }
+                _ => self.build_tree(ns, ex)?,
+            };
             debug!(?id, ?node, "assignment2: binding {}", id);
             ns.bind(id.to_string(), node);
         }
+        Ok(())
+    }
+
+    fn block(
+        &self,
+        ctx: &Context,
+        block: &BlockExpr,
+        params: Option<&HashMap<String, CodeNode>>,
+    ) -> Result<CodeNode, Error> {
+        let ns = ctx.new_child();
+        debug!(?block.local_assignments, "block");
+        self.bind_statements(&ns, &block.local_assignments, params)?;
         self.build_tree(&ns, &block.expression)
     }
 
+    fn module(
+        &self,
+        ctx: &Context,
+        module: &Module,
+        params: Option<&HashMap<String, CodeNode>>,
+    ) -> Result<NodeContent, Error> {
+        let ns = ctx.new_child();
+        self.bind_statements(&ns, &module.assignments, params)?;
+        Ok(NodeContent::HashMap(
+            module
+                .exports
+                .iter()
+                .map(|name| HmEntry {
+                    key: CodeNode::new(NodeContent::Resolved(Value::String((*name).into())), None),
+                    value: ns.get_value(name).expect("exported name is bound"),
+                })
+                .collect(),
+        ))
+    }
+
     fn identifier(&self, ctx: &Context, id: &str, loc: &Span) -> Result<NodeContent, Error> {
         let func_node = ctx
             .get_value(id)
-            .or_else(|| super::functions::lookup(id).map(|func| builtin_func_node(func)))
+            .or_else(|| {
+                super::functions::lookup(id, &self.host)
+                    .map(|func| CodeNode::new(NodeContent::Resolved(Value::Func(func)), None))
+            })
             .ok_or_else(|| ErrorWithLocation {
                 location: Some(loc.into()),
                 message: format!("Variable '{}' is not defined", id),
@@ -205,34 +358,266 @@ impl NodeTreeBuilder {
         )))
     }
 
-    fn import(&self, file_name: &str, ctx: &Context, location: &Span) -> Result<CodeNode, Error> {
-        let final_file_name = Path::new(&*location.extra)
-            .parent()
-            .unwrap()
-            .join(file_name)
-            .canonicalize()
-            .unwrap();
-        let file_name_str = final_file_name.to_str().expect("absolute path").to_string();
-        if let Some(node) = ctx.get_value(&file_name_str) {
+    fn param(
+        &self,
+        ctx: &Context,
+        name: &str,
+        default: &Option<Box<ExprWithLocation>>,
+        params: &HashMap<String, CodeNode>,
+        location: &Span,
+    ) -> Result<CodeNode, Error> {
+        match (params.get(name), default) {
+            (Some(node), _) => Ok(node.clone()),
+            (None, Some(default)) => self.build_tree(ctx, default),
+            (None, None) => Err(ErrorWithLocation {
+                location: Some(location.into()),
+                message: format!("Parameter '{}' is not set", name),
+            }),
+        }
+    }
+
+    fn import(
+        &self,
+        file_name: &str,
+        params: &[HashMapEntry],
+        ctx: &Context,
+        location: &Span,
+    ) -> Result<CodeNode, Error> {
+        let import_error = |message: String| ErrorWithLocation {
+            location: Some(location.into()),
+            message: format!("Cannot import '{}': {}", file_name, message),
+        };
+        let final_file_name = self
+            .import_path(file_name, location)
+            .map_err(|e| import_error(e.to_string()))?;
+        self.host
+            .dependencies
+            .add_import(PathBuf::from(&*location.extra), final_file_name.clone());
+        let file_name_str = final_file_name
+            .to_str()
+            .ok_or_else(|| import_error("path is not valid UTF-8".to_string()))?
+            .to_string();
+        let param_nodes = params
+            .iter()
+            .map(|HashMapEntry { key, value, .. }| {
+                let name = match &key.inner {
+                    Expr::Value(ConfigValue::String(s)) => match s.as_slice() {
+                        [ConfigString::Raw(name)] => Some(*name),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+                .ok_or_else(|| ErrorWithLocation {
+                    location: Some((&key.location).into()),
+                    message: "Parameter name must be a constant string".to_string(),
+                })?;
+                Ok((name, &key.location, self.build_tree(ctx, value)?))
+            })
+            .collect::<Result<Vec<(&str, &Span, CodeNode)>, Error>>()?;
+        // Imports with parameters are cached only if all parameter values are constant.
+        let cache_key = param_nodes
+            .iter()
+            .map(|(name, _, node)| node.resolved_value().map(|v| format!("{}={:?}", name, v)))
+            .collect::<Option<Vec<String>>>()
+            .map(|mut key_params| {
+                if key_params.is_empty() {
+                    file_name_str.clone()
+                } else {
+                    key_params.sort();
+                    format!("{}?{}", file_name_str, key_params.join("&"))
+                }
+            });
+        // The input is recorded also if the import is served from the cache.
+        let content = read_to_string(&final_file_name);
+        self.host
+            .inputs
+            .file(&final_file_name, content.as_deref().ok())
+            .map_err(import_error)?;
+        if let Some(node) = cache_key
+            .as_ref()
+            .and_then(|x| self.host.import_cache.get(x))
+        {
             debug!(%file_name_str, "Found in cache");
             return Ok(node);
         }
-        let content = read_to_string(&final_file_name).map_err(|e| ErrorWithLocation {
-            location: Some(location.into()),
-            message: format!(
-                "Cannot read file '{}': {}",
-                final_file_name.to_str().unwrap(),
-                &e
-            ),
-        })?;
-        let (_, expr) = parse_unit(Span::new_extra(
-            &content,
-            final_file_name.to_str().unwrap().into(),
-        ))?;
-        let node = NodeTreeBuilder.build_tree(&Context::empty(), &expr)?;
-        ctx.bind(file_name_str, node.clone());
+        let import_location: Location = location.into();
+        if *import_location.source == *file_name_str
+            || self
+                .import_chain
+                .iter()
+                .any(|x| *x.source == *file_name_str)
+        {
+            let chain: Vec<String> = self
+                .import_chain
+                .iter()
+                .chain(std::iter::once(&import_location))
+                .map(|x| format!("{}:{}", x.source, x.line))
+                .chain(std::iter::once(file_name_str))
+                .collect();
+            return Err(ErrorWithLocation {
+                location: Some(import_location),
+                message: format!("Import cycle: {}", chain.join(" -> ")),
+            });
+        }
+        let content = content.map_err(|e| import_error(e.to_string()))?;
+        let data_file = matches!(
+            final_file_name.extension().and_then(|x| x.to_str()),
+            Some("json") | Some("yaml") | Some("yml") | Some("toml")
+        );
+        if data_file && !params.is_empty() {
+            return Err(import_error(
+                "data files do not take parameters".to_string(),
+            ));
+        }
+        let node = match final_file_name.extension().and_then(|x| x.to_str()) {
+            Some("json") => data_node(serde_json::from_str(&content), "JSON", import_error)?,
+            Some("yaml") | Some("yml") => {
+                data_node(serde_yaml::from_str(&content), "YAML", import_error)?
+            }
+            Some("toml") => data_node(toml::de::from_str(&content), "TOML", import_error)?,
+            _ => {
+                let expr =
+                    parse_complete_unit(Span::new_extra(&content, file_name_str.as_str().into()))?;
+                let declared = declared_params(&expr);
+                let mut values = HashMap::with_capacity(param_nodes.len());
+                for (name, key_location, node) in param_nodes {
+                    if !declared.contains(&name) {
+                        return Err(ErrorWithLocation {
+                            location: Some(key_location.into()),
+                            message: format!("'{}' is not a parameter of '{}'", name, file_name),
+                        });
+                    }
+                    values.insert(name.to_string(), node);
+                }
+                let mut import_chain = self.import_chain.clone();
+                import_chain.push(import_location);
+                NodeTreeBuilder {
+                    import_chain,
+                    lib_dirs: self.lib_dirs.clone(),
+                    host: self.host.clone(),
+                }
+                .build_file(&Context::empty(), &expr, &values)?
+            }
+        };
+        if let Some(key) = cache_key {
+            self.host.import_cache.insert(key, node.clone());
+        }
         Ok(node)
     }
+
+    fn selective_import(
+        &self,
+        file_name: &str,
+        name: &str,
+        ctx: &Context,
+        location: &Span,
+    ) -> Result<CodeNode, Error> {
+        self.import(file_name, &[], ctx, location)?
+            .field(name)
+            .ok_or_else(|| ErrorWithLocation {
+                location: Some(location.into()),
+                message: format!("'{}' is not exported by '{}'", name, file_name),
+            })
+    }
+
+    fn import_glob(
+        &self,
+        pattern: &str,
+        ctx: &Context,
+        location: &Span,
+    ) -> Result<NodeContent, Error> {
+        let glob_error = |message: String| ErrorWithLocation {
+            location: Some(location.into()),
+            message: format!("Cannot import '{}': {}", pattern, message),
+        };
+        let full_pattern = Path::new(&*location.extra)
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(pattern);
+        let full_pattern = full_pattern
+            .to_str()
+            .ok_or_else(|| glob_error("path is not valid UTF-8".to_string()))?;
+        let mut entries: Vec<HmEntry> = vec![];
+        // glob yields the paths in alphabetical order.
+        for path in glob::glob(full_pattern).map_err(|e| glob_error(e.to_string()))? {
+            // Paths matched relative to the working directory, e.g. from `<input>`,
+            // must not be looked up in the library directories.
+            let path = path
+                .map_err(|e| glob_error(e.to_string()))?
+                .canonicalize()
+                .map_err(|e| glob_error(e.to_string()))?;
+            let path_str = path
+                .to_str()
+                .ok_or_else(|| glob_error("path is not valid UTF-8".to_string()))?;
+            let stem = path
+                .file_stem()
+                .and_then(|x| x.to_str())
+                .unwrap_or_default();
+            if entries
+                .iter()
+                .any(|x| x.key.resolved_value() == Some(&Value::String(stem.into())))
+            {
+                return Err(glob_error(format!(
+                    "more than one file with the name '{}'",
+                    stem
+                )));
+            }
+            entries.push(HmEntry {
+                key: CodeNode::new(NodeContent::Resolved(Value::String(stem.into())), None),
+                value: self.import(path_str, &[], ctx, location)?,
+            });
+        }
+        Ok(NodeContent::HashMap(entries))
+    }
+
+    // Paths that are not explicitly relative are looked up in the library directories first.
+    fn import_path(&self, file_name: &str, location: &Span) -> std::io::Result<PathBuf> {
+        let path = Path::new(file_name);
+        let explicit = matches!(
+            path.components().next(),
+            Some(Component::CurDir) | Some(Component::ParentDir)
+        );
+        if !explicit && path.is_relative() {
+            if let Some(found) = self
+                .lib_dirs
+                .iter()
+                .find_map(|dir| dir.join(path).canonicalize().ok())
+            {
+                return Ok(found);
+            }
+        }
+        Path::new(&*location.extra)
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(path)
+            .canonicalize()
+    }
+}
+
+// Names of the parameters declared in the top-level statements.
+fn declared_params<'a>(expr: &ExprWithLocation<'a>) -> Vec<&'a str> {
+    let assignments = match &expr.inner {
+        Expr::Block(block) => &block.local_assignments,
+        Expr::Module(module) => &module.assignments,
+        _ => return vec![],
+    };
+    assignments
+        .iter()
+        .filter_map(|x| match &x.1.inner {
+            Expr::Param(name, _) => Some(*name),
+            _ => None,
+        })
+        .collect()
+}
+
+fn data_node<E: std::fmt::Display>(
+    parsed: Result<Value, E>,
+    format: &str,
+    import_error: impl Fn(String) -> Error,
+) -> Result<CodeNode, Error> {
+    parsed
+        .map(|x| CodeNode::new(NodeContent::Resolved(x), None))
+        .map_err(|e| import_error(format!("cannot parse {}: {}", format, e)))
 }
 
 fn builtin_func_node(func: &'static FunctionSig) -> CodeNode {
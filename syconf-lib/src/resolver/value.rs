@@ -92,6 +92,23 @@ impl Value {
         }
     }
 
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Bool(_) => "bool",
+            Value::Number(Number::Int(_)) => "int",
+            Value::Number(Number::Float(_)) => "float",
+            Value::String(_) => "string",
+            Value::HashMap(_) => "hashmap",
+            Value::List(_) => "list",
+            Value::Func(_) => "function",
+        }
+    }
+
+    /// Names of the methods that can be called on the value.
+    pub fn method_names(&self) -> &'static [&'static str] {
+        crate::resolver::methods::names(self)
+    }
+
     pub fn to_serializable(&self) -> SerializableValue {
         match self {
             Value::Bool(x) => SerializableValue::Bool(*x),
@@ -135,6 +152,7 @@ impl fmt::Debug for Func {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.0 {
             FuncInner::BuiltInFunction(_) => f.write_str("<func>"),
+            FuncInner::BuiltInClosure(_) => f.write_str("<func>"),
             FuncInner::BuiltInMethod(_) => f.write_str("<func>"),
             FuncInner::UserDefined(ud) => {
                 f.write_str(format!("user_func:{:?}", ud.definition.as_ref()).as_str())
@@ -148,6 +166,12 @@ impl Func {
         Self(FuncInner::BuiltInFunction(func))
     }
 
+    pub fn new_closure(
+        func: impl Fn(&[Value]) -> Result<Value, Error> + Send + Sync + 'static,
+    ) -> Self {
+        Self(FuncInner::BuiltInClosure(Arc::new(func)))
+    }
+
     pub fn new_method(method: Method) -> Self {
         Self(FuncInner::BuiltInMethod(method))
     }
@@ -159,9 +183,18 @@ impl Func {
         }))
     }
 
+    /// Argument names of a user defined function.
+    pub fn argument_names(&self) -> Option<&[String]> {
+        match &self.0 {
+            FuncInner::UserDefined(ud) => ud.definition.argument_names.as_deref(),
+            _ => None,
+        }
+    }
+
     pub fn call(&self, args: &[Value]) -> Result<Value, ErrorWithLocation> {
         match &self.0 {
             FuncInner::BuiltInFunction(func) => func(args),
+            FuncInner::BuiltInClosure(func) => func(args),
             FuncInner::BuiltInMethod(method) => method.call(args),
             FuncInner::UserDefined(ud) => ud.call(args),
         }
@@ -173,6 +206,7 @@ pub type FunctionSig = dyn Fn(&[Value]) -> Result<Value, Error> + Send + Sync;
 #[derive(Clone)]
 enum FuncInner {
     BuiltInFunction(&'static FunctionSig),
+    BuiltInClosure(Arc<FunctionSig>),
     BuiltInMethod(Method),
     UserDefined(UserDefinedFunction),
 }
@@ -0,0 +1,123 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::resolver::Value;
+
+/// A difference between two values at a path like `spec.containers[0].image`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    Added {
+        path: String,
+        value: Value,
+    },
+    Removed {
+        path: String,
+        value: Value,
+    },
+    Modified {
+        path: String,
+        old: Value,
+        new: Value,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added { path, value } => write!(f, "+ {}: {}", path, compact(value)),
+            Change::Removed { path, value } => write!(f, "- {}: {}", path, compact(value)),
+            Change::Modified { path, old, new } => {
+                write!(f, "{}: {} -> {}", path, compact(old), compact(new))
+            }
+        }
+    }
+}
+
+fn compact(value: &Value) -> String {
+    serde_json::to_string(&value.to_serializable()).unwrap()
+}
+
+/// Compares two values structurally, i.e. independently of the order of hashmap keys.
+/// Hashmaps are compared by keys and lists by indices.
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_at(String::new(), old, new, &mut changes);
+    changes
+}
+
+fn diff_at(path: String, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (Value::HashMap(old), Value::HashMap(new)) => {
+            let keys: BTreeSet<_> = old.keys().chain(new.keys()).collect();
+            for key in keys {
+                let path = if path.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match (old.get(key), new.get(key)) {
+                    (Some(old), Some(new)) => diff_at(path, old, new, changes),
+                    (Some(old), None) => changes.push(Change::Removed {
+                        path,
+                        value: old.clone(),
+                    }),
+                    (None, Some(new)) => changes.push(Change::Added {
+                        path,
+                        value: new.clone(),
+                    }),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        (Value::List(old), Value::List(new)) => {
+            for ix in 0..old.len().max(new.len()) {
+                let path = format!("{}[{}]", path, ix);
+                match (old.get(ix), new.get(ix)) {
+                    (Some(old), Some(new)) => diff_at(path, old, new, changes),
+                    (Some(old), None) => changes.push(Change::Removed {
+                        path,
+                        value: old.clone(),
+                    }),
+                    (None, Some(new)) => changes.push(Change::Added {
+                        path,
+                        value: new.clone(),
+                    }),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        (old, new) if old != new => changes.push(Change::Modified {
+            path: if path.is_empty() {
+                ".".to_string()
+            } else {
+                path
+            },
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+#[test]
+fn diff_values() {
+    let old = crate::parse_string(
+        r#"{ spec: { replicas: 2, ports: [80, 443] }, metadata: { name: "api", zone: "a" } }"#,
+    )
+    .unwrap();
+    let new = crate::parse_string(
+        r#"{ metadata: { labels: { team: "ops" }, name: "api" }, spec: { ports: [80], replicas: 3 } }"#,
+    )
+    .unwrap();
+    let changes: Vec<String> = diff(&old, &new).iter().map(ToString::to_string).collect();
+    assert_eq!(
+        changes,
+        vec![
+            r#"+ metadata.labels: {"team":"ops"}"#,
+            r#"- metadata.zone: "a""#,
+            "- spec.ports[1]: 443",
+            "spec.replicas: 2 -> 3",
+        ]
+    );
+    assert!(diff(&old, &old).is_empty());
+}
@@ -0,0 +1,140 @@
+use std::fmt::Write;
+
+use crate::parser::Number;
+use crate::resolver::Value;
+
+const INDENT: &str = "    ";
+
+impl Value {
+    /// Formats the value in the syconf syntax. Hashmaps and lists with nested collections
+    /// are spread over several lines.
+    pub fn to_syconf_string(&self) -> String {
+        let mut out = String::new();
+        write_value(&mut out, self, 0);
+        out
+    }
+}
+
+fn write_value(out: &mut String, value: &Value, level: usize) {
+    match value {
+        Value::Bool(x) => write!(out, "{}", x).unwrap(),
+        Value::Number(Number::Int(x)) => write!(out, "{}", x).unwrap(),
+        Value::Number(Number::Float(x)) => write!(out, "{:?}", x).unwrap(),
+        Value::String(x) => write_string(out, x),
+        Value::Func(func) => match func.argument_names() {
+            Some(names) => write!(out, "({}) => ...", names.join(", ")).unwrap(),
+            None => out.push_str("<function>"),
+        },
+        Value::List(list) if list.iter().all(is_scalar) => {
+            out.push('[');
+            for (ix, x) in list.iter().enumerate() {
+                if ix > 0 {
+                    out.push_str(", ");
+                }
+                write_value(out, x, level);
+            }
+            out.push(']');
+        }
+        Value::List(list) => {
+            out.push_str("[\n");
+            for x in list.iter() {
+                out.push_str(&INDENT.repeat(level + 1));
+                write_value(out, x, level + 1);
+                out.push_str(",\n");
+            }
+            out.push_str(&INDENT.repeat(level));
+            out.push(']');
+        }
+        Value::HashMap(hm) if hm.is_empty() => out.push_str("{}"),
+        Value::HashMap(hm) => {
+            let mut keys: Vec<_> = hm.keys().collect();
+            keys.sort();
+            out.push_str("{\n");
+            for key in keys {
+                out.push_str(&INDENT.repeat(level + 1));
+                if is_identifier(key) {
+                    out.push_str(key);
+                } else {
+                    write_string(out, key);
+                }
+                out.push_str(": ");
+                write_value(out, &hm[key], level + 1);
+                out.push_str(",\n");
+            }
+            out.push_str(&INDENT.repeat(level));
+            out.push('}');
+        }
+    }
+}
+
+// Strings that would be interpolated or end early in double quotes are written as raw strings,
+// with enough `#`s around the quotes to not end at a quote inside the string.
+fn write_string(out: &mut String, s: &str) {
+    if !s.contains('"') && !s.contains("${") {
+        write!(out, "\"{}\"", s).unwrap();
+        return;
+    }
+    let mut hashes = String::new();
+    while s.contains(&format!("'{}", hashes)) {
+        hashes.push('#');
+    }
+    write!(out, "{}'{}'{}", hashes, s, hashes).unwrap();
+}
+
+fn is_scalar(value: &Value) -> bool {
+    !matches!(value, Value::List(_) | Value::HashMap(_))
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|x| x.is_ascii_alphabetic() || x == '_')
+        && chars.all(|x| x.is_ascii_alphanumeric() || x == '_')
+}
+
+#[test]
+fn syconf_string() {
+    let value = crate::parse_string(
+        r#"{ b: [1, 2.5, "x"], a: { "my-key": true, list: [{}, [ ]] }, f: (x, y) => x }"#,
+    )
+    .unwrap();
+    let formatted = value.to_syconf_string();
+    assert_eq!(
+        formatted,
+        r#"{
+    a: {
+        list: [
+            {},
+            [],
+        ],
+        "my-key": true,
+    },
+    b: [1, 2.5, "x"],
+    f: (x, y) => ...,
+}"#
+    );
+    let value =
+        crate::parse_string(r#"{ b: [1, 2.5, "x"], a: { "my-key": true, "ключ": 1 } }"#).unwrap();
+    assert_eq!(
+        crate::parse_string(&value.to_syconf_string()).unwrap(),
+        value
+    );
+
+    let value = crate::parse_string(
+        r###"{ #'say "hi"'#: #"say "hi""#, i: '${x}', q: "it's", r: ##'"it's" '#'##, e: "" }"###,
+    )
+    .unwrap();
+    let formatted = value.to_syconf_string();
+    assert_eq!(
+        formatted,
+        r###"{
+    e: "",
+    i: '${x}',
+    q: "it's",
+    r: ##'"it's" '#'##,
+    'say "hi"': 'say "hi"',
+}"###
+    );
+    assert_eq!(crate::parse_string(&formatted).unwrap(), value);
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::resolver::methods::hashmap::merge_raw;
+use crate::resolver::{Error, Value};
+
+// Splits a path like `spec.containers[0].image` or `spec.containers.0.image` into its segments.
+fn segments(path: &str) -> Vec<String> {
+    path.replace('[', ".")
+        .replace(']', "")
+        .split('.')
+        .filter(|x| !x.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+impl Value {
+    /// Returns the value at the given path, e.g. `spec.containers[0].image`.
+    pub fn get_path(&self, path: &str) -> Result<&Value, Error> {
+        let mut current = self;
+        for segment in segments(path) {
+            current = match current {
+                Value::HashMap(hm) => hm.get(segment.as_str()),
+                Value::List(list) => segment.parse::<usize>().ok().and_then(|ix| list.get(ix)),
+                _ => None,
+            }
+            .ok_or_else(|| format!("'{}' has no element '{}'", path, segment))?;
+        }
+        Ok(current)
+    }
+
+    /// Returns a copy of the value with `value` set at the given path.
+    /// Hashmaps are merged in the same way as by the `merge` method.
+    pub fn merge_at_path(&self, path: &str, value: Value) -> Result<Value, Error> {
+        merge_at(Some(self), &segments(path), value)
+            .map_err(|e| format!("Cannot set '{}': {}", path, e.message).into())
+    }
+}
+
+fn merge_at(target: Option<&Value>, path: &[String], value: Value) -> Result<Value, Error> {
+    let (segment, rest) = match path.split_first() {
+        Some(x) => x,
+        None => {
+            return Ok(match (target, value) {
+                (Some(Value::HashMap(dest)), Value::HashMap(src)) => {
+                    Value::HashMap(Arc::new(merge_raw(dest.as_ref().clone(), &src)))
+                }
+                (_, value) => value,
+            })
+        }
+    };
+    match target {
+        Some(Value::List(list)) => {
+            let ix = segment
+                .parse::<usize>()
+                .map_err(|_| format!("'{}' is not a list index", segment))?;
+            check!(
+                ix < list.len(),
+                "index {} is out of range for a list of {} elements",
+                ix,
+                list.len()
+            );
+            let mut out = list.to_vec();
+            out[ix] = merge_at(Some(&list[ix]), rest, value)?;
+            Ok(Value::List(out.into()))
+        }
+        Some(Value::HashMap(_)) | None => {
+            let mut out = match target {
+                Some(Value::HashMap(hm)) => hm.as_ref().clone(),
+                _ => HashMap::new(),
+            };
+            let new_value = merge_at(out.get(segment.as_str()), rest, value)?;
+            out.insert(segment.as_str().into(), new_value);
+            Ok(Value::HashMap(Arc::new(out)))
+        }
+        Some(_) => Err(format!("cannot set '{}' of a non-collection value", segment).into()),
+    }
+}
+
+#[test]
+fn get_value_at_path() {
+    let value = crate::parse_string(r#"{ spec: { containers: [{ name: "app" }] } }"#).unwrap();
+    assert_eq!(
+        value.get_path("spec.containers.0.name").unwrap(),
+        &Value::String("app".into())
+    );
+    assert_eq!(
+        value.get_path("spec.containers[0].name").unwrap(),
+        &Value::String("app".into())
+    );
+    assert_eq!(
+        value.get_path("spec.containers.1").unwrap_err().message,
+        "'spec.containers.1' has no element '1'"
+    );
+    assert_eq!(
+        value.get_path("spec.volumes").unwrap_err().message,
+        "'spec.volumes' has no element 'volumes'"
+    );
+    assert_eq!(
+        value
+            .get_path("spec.containers.0.name.first")
+            .unwrap_err()
+            .message,
+        "'spec.containers.0.name.first' has no element 'first'"
+    );
+}
+
+#[test]
+fn merge_value_at_path() {
+    let value = crate::parse_string(
+        r#"
+        {
+            spec: {
+                replicas: 1,
+                containers: [{ name: "app", image: "app:1" }],
+            }
+        }
+    "#,
+    )
+    .unwrap();
+    let value = value
+        .merge_at_path("spec.containers[0].image", Value::String("app:2".into()))
+        .unwrap()
+        .merge_at_path(
+            "spec",
+            crate::parse_string(r#"{ replicas: 3, paused: true }"#).unwrap(),
+        )
+        .unwrap()
+        .merge_at_path("metadata.labels.team", Value::String("ops".into()))
+        .unwrap();
+    assert_eq!(
+        value,
+        crate::parse_string(
+            r#"
+        {
+            spec: {
+                replicas: 3,
+                paused: true,
+                containers: [{ name: "app", image: "app:2" }],
+            },
+            metadata: { labels: { team: "ops" } },
+        }
+    "#
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        value
+            .merge_at_path("spec.containers[1]", Value::Bool(true))
+            .err()
+            .unwrap()
+            .message,
+        "Cannot set 'spec.containers[1]': index 1 is out of range for a list of 1 elements"
+    );
+}
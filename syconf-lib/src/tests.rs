@@ -3,8 +3,44 @@ use crate::resolver::Value;
 use std::collections::HashMap;
 
 use crate::parser::number::Number;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// Temporary directory of a test, it is removed when dropped, also if the test fails.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("syconf-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        TempDir(path.canonicalize().unwrap())
+    }
+
+    /// Writes the file, creating its parent directories, and returns its path.
+    fn write(&self, name: &str, content: impl AsRef<[u8]>) -> String {
+        let path = self.0.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
 #[test]
 fn error_location() {
     let err = parse_string(
@@ -159,6 +195,24 @@ fn comments() {
     );
 }
 
+#[test]
+fn block_comments() {
+    assert_eq!(
+        parse_string(
+            r#"
+    /*
+    /* nested */
+    44 == 45
+    */
+    /// doc comment
+    44 == /* inline */ 44
+    "#
+        )
+        .unwrap(),
+        Value::Bool(true)
+    );
+}
+
 #[test]
 fn function_definition() {
     assert_eq!(
@@ -240,3 +294,632 @@ fn negative_values() {
         Value::Bool(true)
     );
 }
+
+#[test]
+fn duplicate_hashmap_keys() {
+    let err = parse_string(
+        r#"
+    {
+        port: 80,
+        port: 8080,
+    }
+    "#,
+    )
+    .err()
+    .unwrap();
+    assert_eq!(err.location.unwrap().line, 4);
+    assert_eq!(
+        err.message,
+        "Duplicate key 'port', first defined at <input>:3:9"
+    );
+
+    let err = parse_string(
+        r#"
+    let x = "port"
+    in
+    {
+        port: 80,
+        "${x}": 8080,
+    }
+    "#,
+    )
+    .err()
+    .unwrap();
+    assert_eq!(err.location.unwrap().line, 6);
+}
+
+#[test]
+fn assertion() {
+    assert_eq!(
+        parse_string(
+            r#"
+    let replicas = 3
+    in
+    assert replicas > 0 : "replicas must be positive" in
+    assert replicas < 10 in
+    replicas
+    "#
+        )
+        .unwrap(),
+        Value::Number(Number::Int(3))
+    );
+
+    let err = parse_string(
+        r#"
+    let replicas = 0
+    in
+    assert replicas > 0 : "replicas must be positive" in replicas
+    "#,
+    )
+    .err()
+    .unwrap();
+    assert_eq!(err.message, "Assertion failed: replicas must be positive");
+    assert_eq!(err.location.unwrap().line, 4);
+}
+
+#[test]
+fn try_else() {
+    assert_eq!(
+        parse_string(
+            r#"
+    let config = try "a: [".parse_yaml() else {}
+    let port = try {a: 1}.port else 8080
+    in
+    config == {} and port == 8080
+    "#
+        )
+        .unwrap(),
+        Value::Bool(true)
+    );
+    assert_eq!(
+        parse_string(
+            r#"
+    try error("boom") else (err) => "recovered: ${err}"
+    "#
+        )
+        .unwrap(),
+        Value::String("recovered: boom".into())
+    );
+    assert_eq!(
+        parse_string(
+            r#"
+    let handler = (x) => x
+    in
+    (try {}.h else handler)("not the error")
+    "#
+        )
+        .unwrap(),
+        Value::String("not the error".into())
+    );
+    assert_eq!(
+        parse_string(
+            r#"
+    try import "./does-not-exist.sy" else "default"
+    "#
+        )
+        .unwrap(),
+        Value::String("default".into())
+    );
+}
+
+#[test]
+fn heredoc_string() {
+    assert_eq!(
+        parse_string(
+            r#"
+    let script = "echo 1
+echo 2"
+    let name = "job"
+    in
+    """
+        name: ${name}
+        run: |
+          ${script}
+        """
+    "#
+        )
+        .unwrap(),
+        Value::String("name: job\nrun: |\n  echo 1\n  echo 2".into())
+    );
+    assert_eq!(
+        parse_string(
+            r#"
+    """+
+      line
+    """
+    "#
+        )
+        .unwrap(),
+        Value::String("line\n".into())
+    );
+    assert_eq!(
+        parse_string(
+            r#"
+    let a = "key"
+    let b = "x
+y"
+    in
+    """
+      ${a}: ${b}
+      """
+    "#
+        )
+        .unwrap(),
+        Value::String("key: x\n     y".into())
+    );
+}
+
+#[test]
+fn import_cycle() {
+    let dir = TempDir::new("import-cycle");
+    dir.write("a.sy", "let x = 1\nin\n import \"./b.sy\"");
+    dir.write("b.sy", "import \"./a.sy\"");
+
+    let err = crate::parse_file(dir.join("a.sy").to_str().unwrap())
+        .err()
+        .unwrap();
+    let a = dir.join("a.sy");
+    let b = dir.join("b.sy");
+    assert_eq!(
+        err.message,
+        format!(
+            "Import cycle: {}:3 -> {}:1 -> {}",
+            a.display(),
+            b.display(),
+            a.display()
+        )
+    );
+    assert_eq!(&*err.location.unwrap().source, b.to_str().unwrap());
+}
+
+#[test]
+fn import_missing_file() {
+    let err = parse_string(
+        r#"
+    let x = import "./does-not-exist.sy"
+    in
+    x
+    "#,
+    )
+    .err()
+    .unwrap();
+    assert!(err
+        .message
+        .starts_with("Cannot import './does-not-exist.sy': "));
+    assert_eq!(err.location.unwrap().line, 2);
+
+    let err = crate::parse_file("./does-not-exist.sy").err().unwrap();
+    assert!(err.message.starts_with("Cannot read file"));
+}
+
+#[test]
+fn import_data_files() {
+    let dir = TempDir::new("import-data");
+    dir.write("data/values.json", r#"{"a": [1, 2]}"#);
+    dir.write("data/values.yaml", "b: text");
+    dir.write("data/values.toml", "c = 1.5");
+    dir.write("data/broken.yaml", "d: [");
+    dir.write(
+        "main.sy",
+        r#"
+        let json = import "./data/values.json"
+        let yaml = import "./data/values.yaml"
+        let toml = import "./data/values.toml"
+        in
+        json.a == [1, 2] and yaml.b == "text" and toml.c == 1.5
+        "#,
+    );
+    dir.write("broken.sy", r#"import "./data/broken.yaml""#);
+
+    assert_eq!(
+        crate::parse_file(dir.join("main.sy").to_str().unwrap()).unwrap(),
+        Value::Bool(true)
+    );
+    let err = crate::parse_file(dir.join("broken.sy").to_str().unwrap())
+        .err()
+        .unwrap();
+    assert!(err
+        .message
+        .starts_with("Cannot import './data/broken.yaml': cannot parse YAML"));
+}
+
+#[test]
+fn import_lib_dirs() {
+    let dir = TempDir::new("lib-dirs");
+    dir.write("lib/k8s/deployment.sy", r#""from lib""#);
+    dir.write("app/k8s/deployment.sy", r#""local""#);
+    dir.write(
+        "app/main.sy",
+        r#"[import "k8s/deployment.sy", import "./k8s/deployment.sy"]"#,
+    );
+
+    let options = crate::Options {
+        lib_dirs: vec![dir.join("lib")],
+        ..Default::default()
+    };
+    assert_eq!(
+        crate::parse_file_with_options(dir.join("app/main.sy").to_str().unwrap(), &options)
+            .unwrap(),
+        Value::List(
+            vec![
+                Value::String("from lib".into()),
+                Value::String("local".into())
+            ]
+            .into()
+        )
+    );
+}
+
+#[test]
+fn module_imports() {
+    let dir = TempDir::new("modules");
+    dir.write(
+        "k8s.sy",
+        r#"
+        let prefix = "app-"
+        export let deployment = (name) => "${prefix}${name}"
+        export let service = "svc"
+        export let broken = error("never evaluated")
+        "#,
+    );
+    dir.write("values.yaml", "replicas: 3");
+    dir.write(
+        "main.sy",
+        r#"
+        import { deployment, service } from "./k8s.sy"
+        import { replicas } from "./values.yaml"
+        in
+        [deployment("web"), service, replicas]
+        "#,
+    );
+    dir.write(
+        "unknown.sy",
+        r#"
+        import { deployment, ingress } from "./k8s.sy"
+        in
+        deployment
+        "#,
+    );
+
+    assert_eq!(
+        crate::parse_file(dir.join("main.sy").to_str().unwrap()).unwrap(),
+        Value::List(
+            vec![
+                Value::String("app-web".into()),
+                Value::String("svc".into()),
+                Value::Number(Number::Int(3)),
+            ]
+            .into()
+        )
+    );
+    let err = crate::parse_file(dir.join("unknown.sy").to_str().unwrap())
+        .err()
+        .unwrap();
+    assert_eq!(err.message, "'ingress' is not exported by './k8s.sy'");
+    let loc = err.location.unwrap();
+    assert_eq!((loc.line, loc.column), (2, 30));
+}
+
+#[test]
+fn module_requires_exports() {
+    let mut hm = HashMap::new();
+    hm.insert("exporter".into(), Value::Number(Number::Int(1)));
+    hm.insert("b".into(), Value::Number(Number::Int(2)));
+    assert_eq!(
+        parse_string("exporter: 1\nb: 2").unwrap(),
+        Value::HashMap(Arc::new(hm))
+    );
+    // Without exports the bindings are a block that lacks its expression.
+    assert!(parse_string("let a = 1\nlet b = 2").is_err());
+    assert!(parse_string("let a = 1\nexport let b = a").is_ok());
+}
+
+#[test]
+fn import_with_params() {
+    let dir = TempDir::new("import-params");
+    dir.write(
+        "svc.sy",
+        r#"
+        param env
+        param replicas = 1
+        in
+        { name: "svc-${env}", replicas: replicas }
+        "#,
+    );
+    dir.write(
+        "main.sy",
+        r#"
+        let count = 3
+        in
+        [
+            import "./svc.sy" with { env: "prod", replicas: count },
+            import "./svc.sy" with { env: "dev" },
+        ]
+        "#,
+    );
+    dir.write(
+        "unknown.sy",
+        r#"import "./svc.sy" with { env: "dev", region: "eu" }"#,
+    );
+
+    assert_eq!(
+        crate::parse_file(dir.join("main.sy").to_str().unwrap()).unwrap(),
+        crate::parse_string(r#"[{name: "svc-prod", replicas: 3}, {name: "svc-dev", replicas: 1}]"#)
+            .unwrap()
+    );
+    let err = crate::parse_file(dir.join("unknown.sy").to_str().unwrap())
+        .err()
+        .unwrap();
+    assert_eq!(err.message, "'region' is not a parameter of './svc.sy'");
+    let err = crate::parse_file(dir.join("svc.sy").to_str().unwrap())
+        .err()
+        .unwrap();
+    assert_eq!(err.message, "Parameter 'env' is not set");
+
+    // Only the values given by the importer set a parameter, not the bindings in scope.
+    assert_eq!(
+        parse_string("let env = \"outer\" in { param env = \"default\"\n in env }")
+            .unwrap_err()
+            .message,
+        "Parameters can only be declared at the top level of a file"
+    );
+    dir.write("env.sy", "param env = \"default\"\nin env");
+    dir.write("outer.sy", "let env = \"outer\"\nin import \"./env.sy\"");
+    assert_eq!(
+        crate::parse_file(&dir.join("outer.sy").to_string_lossy()).unwrap(),
+        Value::String("default".into())
+    );
+}
+
+#[test]
+fn param_prefixed_keys() {
+    let mut hm = HashMap::new();
+    hm.insert("parameters".into(), Value::Number(Number::Int(1)));
+    hm.insert("b".into(), Value::Number(Number::Int(2)));
+    assert_eq!(
+        parse_string("parameters: 1\nb: 2").unwrap(),
+        Value::HashMap(Arc::new(hm))
+    );
+}
+
+#[test]
+fn import_glob() {
+    let dir = TempDir::new("import-glob");
+    dir.write("services/api.sy", "{ port: 80 }");
+    dir.write("services/web.sy", "{ port: 8080 }");
+    dir.write("services/notes.txt", "not a service");
+    dir.write(
+        "main.sy",
+        r#"import_glob("./services/*.sy").map((name, svc) => [name, svc.port])"#,
+    );
+
+    assert_eq!(
+        crate::parse_file(dir.join("main.sy").to_str().unwrap()).unwrap(),
+        crate::parse_string("{ api: 80, web: 8080 }").unwrap()
+    );
+}
+
+#[test]
+fn import_glob_relative_to_working_directory() {
+    // A source without a file, like `<input>`, globs relative to the working directory.
+    let local = format!("syconf-glob-cwd-{}", std::process::id());
+    std::fs::create_dir_all(&local).unwrap();
+    let local = TempDir(PathBuf::from(local));
+    local.write("services/api.sy", "{ port: 80 }");
+    let lib = TempDir::new("glob-cwd-lib");
+    lib.write(
+        &format!("{}/services/api.sy", local.display()),
+        "{ port: 1 }",
+    );
+
+    let options = crate::Options {
+        lib_dirs: vec![lib.to_path_buf()],
+        ..Default::default()
+    };
+    assert_eq!(
+        crate::parse_string_with_options(
+            &format!(r#"import_glob("{}/services/*.sy")"#, local.display()),
+            &options
+        )
+        .unwrap(),
+        crate::parse_string("{ api: { port: 80 } }").unwrap()
+    );
+}
+
+#[test]
+fn top_level_arguments() {
+    let options = crate::Options {
+        args: Some(
+            vec![
+                ("cluster".to_string(), Value::String("eu1".into())),
+                ("replicas".to_string(), Value::Number(Number::Int(2))),
+            ]
+            .into_iter()
+            .collect(),
+        ),
+        ..Default::default()
+    };
+    assert_eq!(
+        crate::parse_string_with_options(
+            r#"(cluster, replicas) => "${cluster}:${replicas}""#,
+            &options
+        )
+        .unwrap(),
+        Value::String("eu1:2".into())
+    );
+    let err = crate::parse_string_with_options(r#"(cluster, region) => cluster"#, &options)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.message,
+        "Configuration function does not take argument 'replicas'"
+    );
+    let err = crate::parse_string_with_options(r#"{a: 1}"#, &options)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.message,
+        "Arguments are given, but the configuration is not a function"
+    );
+}
+
+#[test]
+fn library_files_and_shared_imports() {
+    let dir = TempDir::new("library");
+    let file = |name: &str, content: &str| dir.write(name, content);
+    let module = file("module.sy", "export let port = 80\nlet x = 1");
+    let params = file("params.sy", "param port = 80\nin { port: port }");
+    let func = file("func.sy", "let x = 1\nin (a) => a + x");
+    let common = file("common.sy", "{ port: 80 }");
+    let a = file("a.sy", r#"import "./common.sy""#);
+    let b = file("b.sy", r#"import "./common.sy""#);
+    for f in &[&module, &params, &func] {
+        assert!(crate::is_library_file(f).unwrap(), "{}", f);
+    }
+    assert!(!crate::is_library_file(&common).unwrap());
+
+    let options = crate::Options::default();
+    let expected = crate::parse_string("{ port: 80 }").unwrap();
+    let values = crate::parse_files_with_options(&[a.as_str(), b.as_str()], &options);
+    assert_eq!(values.len(), 2);
+    for value in values {
+        assert_eq!(value.unwrap(), expected);
+    }
+    // Imports are not cached beyond a batch.
+    file("common.sy", "{ port: 81 }");
+    let expected = crate::parse_string("{ port: 81 }").unwrap();
+    assert_eq!(
+        crate::parse_files_with_options(&[b.as_str()], &options)
+            .remove(0)
+            .unwrap(),
+        expected
+    );
+    assert_eq!(
+        crate::parse_file_with_options(&b, &options).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn dependencies() {
+    let dir = TempDir::new("dependencies");
+    dir.write("common.sy", "{ port: 80 }");
+    dir.write("motd.txt", "hello");
+    dir.write(
+        "main.sy",
+        format!(
+            r#"{{ common: import "./common.sy", motd: read_file("{}") }}"#,
+            dir.join("motd.txt").display()
+        ),
+    );
+
+    let options = crate::Options::default();
+    crate::parse_file_with_options(dir.join("main.sy").to_str().unwrap(), &options).unwrap();
+    assert_eq!(
+        options.dependencies.files(),
+        vec![
+            dir.join("common.sy"),
+            dir.join("main.sy"),
+            dir.join("motd.txt")
+        ]
+    );
+    assert_eq!(
+        options.dependencies.imports(),
+        vec![(dir.join("main.sy"), dir.join("common.sy"))]
+    );
+}
+
+#[test]
+fn inputs_report() {
+    let dir = TempDir::new("inputs");
+    dir.write("common.sy", "{ port: 80 }");
+    dir.write(
+        "main.sy",
+        r#"{
+            common: import "./common.sy",
+            user: getenv("SYCONF_INPUTS_TEST_UNSET", "nobody"),
+            greeting: shell("echo hello"),
+        }"#,
+    );
+    let main = dir.join("main.sy");
+    let main = main.to_str().unwrap();
+
+    let options = crate::Options::default();
+    crate::parse_file_with_options(main, &options).unwrap();
+    let report = options.inputs.report();
+    assert_eq!(
+        report.files.keys().collect::<Vec<_>>(),
+        vec![&dir.join("common.sy"), &dir.join("main.sy")]
+    );
+    assert_eq!(report.env["SYCONF_INPUTS_TEST_UNSET"], None);
+    assert_eq!(
+        report.commands["echo hello"],
+        "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03"
+    );
+
+    let frozen = crate::Options {
+        inputs: crate::Inputs::frozen(report),
+        ..Default::default()
+    };
+    crate::parse_file_with_options(main, &frozen).unwrap();
+    dir.write("common.sy", "{ port: 81 }");
+    let frozen = crate::Options {
+        inputs: frozen.inputs.cleared(),
+        ..Default::default()
+    };
+    assert_eq!(
+        crate::parse_file_with_options(main, &frozen)
+            .unwrap_err()
+            .message,
+        format!(
+            "Cannot import './common.sy': File '{}' differs from the frozen inputs",
+            dir.join("common.sy").display()
+        )
+    );
+
+    // Every file of a batch that imports the changed file fails.
+    let a = dir.write("a.sy", r#"import "./common.sy""#);
+    let b = dir.write("b.sy", r#"import "./common.sy""#);
+    let options = crate::Options::default();
+    for value in crate::parse_files_with_options(&[a.as_str(), b.as_str()], &options) {
+        value.unwrap();
+    }
+    let frozen = crate::Options {
+        inputs: crate::Inputs::frozen(options.inputs.report()),
+        ..Default::default()
+    };
+    dir.write("common.sy", "{ port: 82 }");
+    for value in crate::parse_files_with_options(&[a.as_str(), b.as_str()], &frozen) {
+        assert_eq!(
+            value.unwrap_err().message,
+            format!(
+                "Cannot import './common.sy': File '{}' differs from the frozen inputs",
+                dir.join("common.sy").display()
+            )
+        );
+    }
+}
+
+#[test]
+fn frozen_inputs_are_not_recoverable() {
+    let mut report = crate::InputsReport::default();
+    report.env.insert(
+        "SYCONF_FROZEN_TEST_UNSET".to_string(),
+        Some("a".to_string()),
+    );
+    let options = crate::Options {
+        inputs: crate::Inputs::frozen(report),
+        ..Default::default()
+    };
+    let source = r#"{ u: try getenv("SYCONF_FROZEN_TEST_UNSET") else "fallback" }"#;
+    let err = crate::parse_string_with_options(source, &options).unwrap_err();
+    assert_eq!(
+        err.message,
+        "Environment variable 'SYCONF_FROZEN_TEST_UNSET' differs from the frozen inputs"
+    );
+
+    let mut session = crate::Session::new(&options);
+    assert!(session.eval(&format!("let u = {}", source)).is_ok());
+    assert!(session.eval("u").is_err());
+    assert!(session.eval("1").is_ok());
+}
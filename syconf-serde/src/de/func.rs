@@ -28,6 +28,6 @@ impl Function {
     {
         let value: Value = to_value(input)?;
         let result = self.0.call(&[value])?;
-        from_value(result).map_err(Into::into)
+        from_value(result)
     }
 }
@@ -47,6 +47,7 @@ enum ExternallyTaggedEnum {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn deserialize_struct() {
     let abc: Abc = from_str(
         r#"
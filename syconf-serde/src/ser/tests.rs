@@ -21,6 +21,7 @@ enum Enum1 {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn serialize_struct() {
     let abc = Abc {
         string: "abc".to_owned(),